@@ -0,0 +1,72 @@
+use autodiff::FT;
+use waveformrs::conversion::*;
+
+// |d(a, b) / d(x, y)| from two forward passes
+fn jacobian<F: Fn(FT<f64>, FT<f64>) -> (FT<f64>, FT<f64>)>(map: F, x: f64, y: f64) -> f64 {
+    let (a_x, b_x) = map(FT::var(x), FT::cst(y));
+    let (a_y, b_y) = map(FT::cst(x), FT::var(y));
+    (a_x.deriv() * b_y.deriv() - a_y.deriv() * b_x.deriv()).abs()
+}
+
+fn main() {
+    let (mass_1, mass_2): (f64, f64) = (36.0, 29.0);
+    let (total_mass, mass_ratio) = total_mass_and_mass_ratio(mass_1, mass_2);
+    let chirp = chirp_mass(total_mass, mass_ratio);
+    let eta = symmetric_mass_ratio(mass_ratio);
+    println!(
+        "M {total_mass}, q {mass_ratio:.4}, 1 / q {:.4}",
+        inverse_mass_ratio(mass_ratio)
+    );
+    println!("chirp mass {chirp:.4}, eta {eta:.5}");
+    assert!((mass_ratio_from_symmetric_mass_ratio(eta) - mass_ratio).abs() < 1e-12);
+    let (m1, m2) = component_masses_from_chirp_mass(chirp, mass_ratio);
+    assert!((m1 - mass_1).abs() < 1e-12 && (m2 - mass_2).abs() < 1e-12);
+
+    let (chi_1, chi_2): (f64, f64) = (0.6, -0.3);
+    let effective = chi_eff(mass_ratio, chi_1, chi_2);
+    let antisymmetric = chi_a(chi_1, chi_2);
+    println!(
+        "chi_eff {effective:.4}, chi_a {antisymmetric:.4}, chi_PN {:.4}, chi_p {:.4}",
+        chi_pn(mass_ratio, chi_1, chi_2),
+        chi_p(mass_ratio, 0.3, 0.5)
+    );
+    let (spin_1, spin_2) = component_spins(mass_ratio, effective, antisymmetric);
+    assert!((spin_1 - chi_1).abs() < 1e-12 && (spin_2 - chi_2).abs() < 1e-12);
+
+    let checks = [
+        (
+            "chirp mass",
+            component_masses_to_chirp_mass_jacobian(chirp, mass_ratio),
+            jacobian(component_masses_from_chirp_mass, chirp, mass_ratio),
+        ),
+        (
+            "total mass",
+            component_masses_to_total_mass_jacobian(total_mass, mass_ratio),
+            jacobian(component_masses, total_mass, mass_ratio),
+        ),
+        (
+            "eta",
+            mass_ratio_to_symmetric_mass_ratio_jacobian(mass_ratio),
+            jacobian(
+                |chirp, eta| (chirp, mass_ratio_from_symmetric_mass_ratio(eta)),
+                chirp,
+                eta,
+            ),
+        ),
+        (
+            "chi_eff",
+            COMPONENT_SPINS_TO_CHI_EFF_JACOBIAN,
+            jacobian(
+                |effective, antisymmetric| {
+                    component_spins(FT::cst(mass_ratio), effective, antisymmetric)
+                },
+                effective,
+                antisymmetric,
+            ),
+        ),
+    ];
+    for (name, analytic, numerical) in checks {
+        println!("{name} jacobian {analytic:.6e}, autodiff {numerical:.6e}");
+        assert!((analytic - numerical).abs() < 1e-10 * analytic);
+    }
+}
@@ -0,0 +1,35 @@
+use autodiff::FT;
+use waveformrs::{
+    cosmology::{source_frame_mass, Cosmology},
+    imrphenomd::IMRPhenomD,
+    waveform::Waveform,
+};
+
+fn main() {
+    let cosmology = Cosmology::planck15();
+    for redshift in [0.01f64, 0.1, 0.5, 1.0, 3.0] {
+        let distance = cosmology.luminosity_distance(redshift);
+        let inverse = cosmology.redshift(distance);
+        assert!((inverse - redshift).abs() < 1e-12 * redshift);
+        // the derivatives agree with forward-mode autodiff
+        let dual: FT<f64> = FT::var(redshift);
+        let volume = cosmology.comoving_volume(dual);
+        let differential: f64 = cosmology.differential_comoving_volume(redshift);
+        assert!((volume.deriv() - differential).abs() < 1e-9 * differential);
+        let slope: f64 = cosmology.luminosity_distance(dual).deriv();
+        assert!((slope - cosmology.luminosity_distance_derivative(redshift)).abs() < 1e-9 * slope);
+        println!(
+            "z {redshift}: luminosity distance {distance:.3} Mpc, comoving volume {:.4e} Mpc^3, dV/dz {differential:.4e} Mpc^3",
+            volume.value()
+        );
+    }
+
+    // GW150914 like masses at 410 Mpc
+    let redshift = cosmology.redshift(410.0);
+    let model = IMRPhenomD::from_source_frame(62.0, 0.8, 0.0, 0.0, redshift, &cosmology, 20.0);
+    println!(
+        "z {redshift:.4}: detector-frame mass {:.3}, source-frame mass {:.3}",
+        model.total_mass(),
+        source_frame_mass(model.total_mass(), redshift)
+    );
+}
@@ -0,0 +1,61 @@
+use std::f64::consts::PI;
+
+use waveformrs::{
+    imrphenomd::IMRPhenomD, taylor::TaylorF2, time_domain::signal_duration_bound,
+    waveform::Waveform,
+};
+
+// compare t(f) with a finite difference of the phase and invert it
+fn check<W: Waveform>(name: &str, model: &W, f_lower: f64, f_upper: f64) {
+    println!(
+        "{name}: f_ISCO {:.2} Hz, f_MECO {:.2} Hz, end {:.2} Hz",
+        model.f_isco(),
+        model.f_meco(),
+        model.end_frequency()
+    );
+    // times outside the band are not inverted
+    assert!(model
+        .frequency_of_time(model.time_of_frequency(f_lower) - 1.0, f_lower, f_upper)
+        .is_none());
+    // the strain stops at the end of the model
+    let beyond = model.end_frequency() * 1.01;
+    assert_eq!(model.waveform(&[beyond], 0.0, 0.0).data[0].norm(), 0.0);
+    let phase = |frequency: f64| model.phase(model.orbital_speed(frequency), 0.0);
+    for frequency in [f_lower, 2.0 * f_lower, 4.0 * f_lower] {
+        let time = model.time_of_frequency(frequency);
+        let step = 1e-4 * frequency;
+        let numerical = (phase(frequency + step) - phase(frequency - step)) / (4.0 * PI * step);
+        assert!((time - numerical).abs() < 1e-6 * time.abs().max(1e-3));
+        let inverse = model
+            .frequency_of_time(time, f_lower / 2.0, f_upper)
+            .unwrap();
+        assert!((inverse - frequency).abs() < 1e-9 * frequency);
+        println!(
+            "{name}: t({frequency} Hz) = {time:.4} s, duration bound {:.4} s",
+            signal_duration_bound(model, frequency)
+        );
+    }
+}
+
+fn main() {
+    let f_lower = 20.0;
+    check(
+        "TaylorF2 1.4 + 1.4",
+        &TaylorF2::new(2.8, 1.0, 0.0, 0.0, 100.0, f_lower),
+        f_lower,
+        1000.0,
+    );
+    check(
+        "IMRPhenomD 1.4 + 1.4",
+        &IMRPhenomD::new(2.8, 1.0, 0.0, 0.0, 100.0, f_lower),
+        f_lower,
+        1000.0,
+    );
+    let model = IMRPhenomD::new(65.0, 0.8, 0.3, -0.1, 400.0, f_lower);
+    check("IMRPhenomD 36 + 29", &model, f_lower, 150.0);
+    println!(
+        "IMRPhenomD 36 + 29: ringdown {:.2} Hz, peak {:.2} Hz",
+        model.ringdown_frequency(),
+        model.peak_frequency()
+    );
+}
@@ -0,0 +1,80 @@
+use autodiff::FT;
+use waveformrs::{
+    fisher::{fisher_matrix, parameter_errors, waveform_derivative, FISHER_PARAMETERS},
+    imrphenomd::IMRPhenomD,
+    psd::PowerSpectralDensity,
+    taylor::TaylorF2,
+    waveform::Waveform,
+};
+
+// (ln Mc, eta, chi_1, chi_2, t_c, phi_c, ln D) to the model constructor arguments
+fn model_parameters(parameters: &[f64; 7]) -> (f64, f64, f64) {
+    let eta = parameters[1];
+    let mass_ratio = (1.0 - 2.0 * eta - (1.0 - 4.0 * eta).sqrt()) / (2.0 * eta);
+    let total_mass = parameters[0].exp() * eta.powf(-0.6);
+    (total_mass, mass_ratio, parameters[6].exp())
+}
+
+fn main() {
+    let reference_frequency = 20.0;
+    let parameters = [30.0f64.ln(), 0.24, 0.3, -0.2, 0.0, 0.4, 400.0f64.ln()];
+    let frequencies: Vec<f64> = (0..4000).map(|ii| 20.0 + 0.25 * ii as f64).collect();
+    let psd = PowerSpectralDensity::AdvancedLIGO;
+
+    // compare the autodiff derivatives with central differences
+    for (index, name) in FISHER_PARAMETERS.iter().enumerate() {
+        let derivative = waveform_derivative(
+            &|m, q, c1, c2, d| IMRPhenomD::<FT<f64>>::new(m, q, c1, c2, d, reference_frequency),
+            &parameters,
+            index,
+            &frequencies,
+        );
+        let step = 1e-6;
+        let strain = |offset: f64| {
+            let mut shifted = parameters;
+            shifted[index] += offset;
+            let (total_mass, mass_ratio, distance) = model_parameters(&shifted);
+            IMRPhenomD::new(
+                total_mass,
+                mass_ratio,
+                shifted[2],
+                shifted[3],
+                distance,
+                reference_frequency,
+            )
+            .waveform(&frequencies, shifted[5], shifted[4])
+        };
+        let (upper, lower) = (strain(step), strain(-step));
+        let error = derivative
+            .iter()
+            .zip(upper.data.iter().zip(lower.data.iter()))
+            .map(|(d, (u, l))| (d - (u - l) / (2.0 * step)).norm() / d.norm().max(1e-30))
+            .fold(0.0, f64::max);
+        println!("{name}: maximum relative error {error:.2e}");
+        assert!(
+            error < 1e-4,
+            "autodiff and finite differences disagree for {name}"
+        );
+    }
+
+    let imrd = fisher_matrix(
+        |m, q, c1, c2, d| IMRPhenomD::new(m, q, c1, c2, d, reference_frequency),
+        &parameters,
+        &frequencies,
+        &psd,
+    );
+    let taylor = fisher_matrix(
+        |m, q, c1, c2, d| TaylorF2::new(m, q, c1, c2, d, reference_frequency),
+        &parameters,
+        &frequencies,
+        &psd,
+    );
+    println!("{:>24} {:>12} {:>12}", "", "IMRPhenomD", "TaylorF2");
+    for (name, (a, b)) in FISHER_PARAMETERS.iter().zip(
+        parameter_errors(&imrd)
+            .iter()
+            .zip(parameter_errors(&taylor).iter()),
+    ) {
+        println!("{name:>24} {a:>12.4e} {b:>12.4e}");
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Instant;
+
+use waveformrs::{
+    imrphenomd::IMRPhenomDGenerator,
+    likelihood::GaussianLikelihood,
+    network::Network,
+    parameters::{BinaryParameters, BINARY_PARAMETERS},
+    psd::PowerSpectralDensity,
+    waveform::WaveformGenerator,
+};
+
+fn main() {
+    // the same generator builds the f64 model and the dual-number model for the gradient
+    let model = IMRPhenomDGenerator::new(20.0);
+    let truth = BinaryParameters {
+        total_mass: 65.0,
+        mass_ratio: 0.8,
+        chi_1: 0.3,
+        chi_2: -0.1,
+        ra: 1.375,
+        dec: -1.2108,
+        psi: 0.8,
+        theta_jn: 2.6,
+        phi_c: 0.3,
+        geocent_time: 1126259462.4,
+        luminosity_distance: 500.0,
+    };
+    // the band covers the inspiral, intermediate and merger-ringdown regions of the model
+    let frequencies: Vec<f64> = (0..4017).map(|ii| 20.0 + 0.25 * ii as f64).collect();
+    let network = Network::from_names(&["H1", "L1", "V1"]).unwrap();
+    let epoch = truth.geocent_time - 2.0;
+    let data = network.strain(
+        &model.generate(&truth),
+        &frequencies,
+        &truth.extrinsic(),
+        epoch,
+    );
+    let psds = [
+        PowerSpectralDensity::AdvancedLIGO,
+        PowerSpectralDensity::AdvancedLIGO,
+        PowerSpectralDensity::AdvancedVirgo,
+    ];
+    let likelihood = GaussianLikelihood::new(network, data, &psds, model);
+
+    let parameters = BinaryParameters::from_array([
+        65.4,
+        0.78,
+        0.25,
+        -0.05,
+        1.38,
+        -1.2,
+        0.79,
+        2.55,
+        0.32,
+        1126259462.4003,
+        510.0,
+    ]);
+    let (value, gradient) = likelihood.log_likelihood_ratio_gradient(&parameters);
+    assert!((value - likelihood.log_likelihood_ratio(&parameters)).abs() < 1e-8 * value.abs());
+
+    let steps = [
+        1e-4, 1e-6, 1e-5, 1e-5, 1e-6, 1e-6, 1e-6, 1e-6, 1e-6, 1e-6, 1e-3,
+    ];
+    for (index, name) in BINARY_PARAMETERS.iter().enumerate() {
+        let shifted = |offset: f64| {
+            let mut values = parameters.to_array();
+            values[index] += offset;
+            (
+                values[index],
+                likelihood.log_likelihood_ratio(&BinaryParameters::from_array(values)),
+            )
+        };
+        // use the step actually taken, it is a few ulp for the GPS time
+        let (upper, high) = shifted(steps[index]);
+        let (lower, low) = shifted(-steps[index]);
+        let numerical = (high - low) / (upper - lower);
+        let error = (gradient[index] - numerical).abs() / gradient[index].abs().max(1.0);
+        println!(
+            "{name:>20}: autodiff {:>12.5e}, finite difference {numerical:>12.5e}",
+            gradient[index]
+        );
+        assert!(error < 1e-3, "gradients disagree for {name}");
+    }
+
+    let start = Instant::now();
+    for _ in 0..10 {
+        likelihood.log_likelihood_ratio(&parameters);
+    }
+    let value_time = start.elapsed() / 10;
+    let start = Instant::now();
+    for _ in 0..10 {
+        likelihood.log_likelihood_ratio_gradient(&parameters);
+    }
+    let gradient_time = start.elapsed() / 10;
+    println!("likelihood {value_time:?}, likelihood and gradient {gradient_time:?}");
+}
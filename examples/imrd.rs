@@ -1,15 +1,15 @@
-use num_complex::Complex;
 use std::f64::consts::PI;
 use std::time::Instant;
 
-use waveformrs::{imrphenomd::IMRPhenomD, waveform::Waveform};
+use waveformrs::{imrphenomd::IMRPhenomD, series::FrequencySeries, waveform::Waveform};
 
 fn main() {
-    let total_mass = 90.0;
+    let total_mass: f64 = 90.0;
     let mass_ratio = 0.5;
     let chi_1 = 0.0;
     let chi_2 = 0.0;
     let luminosity_distance = 100.0;
+    let reference_frequency = 20.0;
 
     let delta_f: f64 = 0.25;
     let f_lower: f64 = 20.0;
@@ -20,28 +20,34 @@ fn main() {
     for _ in 0..1000 {
         let frequencies = (0..n_frequencies)
             .map(|i| i as f64 * delta_f)
+            .filter(|&f| f_lower <= f && f <= f_max)
             .collect::<Vec<f64>>();
-        let imrd = IMRPhenomD::new(total_mass, mass_ratio, chi_1, chi_2, luminosity_distance);
-        let _: Vec<Complex<f64>> = frequencies
-            .iter()
-            .map(|&f| {
-                if f < f_lower || f > f_max {
-                    return Complex::<f64>::new(0.0, 0.0);
-                } else {
-                    imrd.waveform_single_frequency(f, 0.1)
-                }
-            })
-            .collect();
+        let imrd = IMRPhenomD::new(
+            total_mass,
+            mass_ratio,
+            chi_1,
+            chi_2,
+            luminosity_distance,
+            reference_frequency,
+        );
+        let _: FrequencySeries = imrd.waveform(&frequencies, 0.1, 0.0);
     }
     let duration = start.elapsed();
     println!("Average evaluation time: {:?}", duration / 1000);
 
-    let imrd = IMRPhenomD::new(total_mass, mass_ratio, chi_1, chi_2, luminosity_distance);
-    let value = imrd.waveform_single_frequency(20.0, 0.1);
+    let imrd = IMRPhenomD::new(
+        total_mass,
+        mass_ratio,
+        chi_1,
+        chi_2,
+        luminosity_distance,
+        reference_frequency,
+    );
+    let value = imrd.waveform_single_frequency(20.0, 0.1, 0.0);
     let phi_ref = value.im.atan2(value.re);
     println!("Inspiral");
     [19.5, 19.75, 20.0, 20.25, 20.5].iter().for_each(|&f| {
-        let value = imrd.waveform_single_frequency(f, 0.1);
+        let value = imrd.waveform_single_frequency(f, 0.1, 0.0);
         println!(
             "{:.5}, {:.8e}",
             (value.im.atan2(value.re) - phi_ref + 2.0 * PI) % (2.0 * PI),
@@ -49,10 +55,10 @@ fn main() {
         );
     });
     println!("Intermediate");
-    let value = imrd.waveform_single_frequency(100.0, 0.1);
+    let value = imrd.waveform_single_frequency(100.0, 0.1, 0.0);
     let phi_ref = value.im.atan2(value.re);
     [99.5, 99.75, 100.0, 100.25, 100.5].iter().for_each(|&f| {
-        let value = imrd.waveform_single_frequency(f, 0.1);
+        let value = imrd.waveform_single_frequency(f, 0.1, 0.0);
         println!(
             "{:.5}, {:.8e}",
             (value.im.atan2(value.re) - phi_ref + 2.0 * PI) % (2.0 * PI),
@@ -60,10 +66,10 @@ fn main() {
         );
     });
     println!("Ringdown");
-    let value = imrd.waveform_single_frequency(200.0, 0.1);
+    let value = imrd.waveform_single_frequency(200.0, 0.1, 0.0);
     let phi_ref = value.im.atan2(value.re);
     [199.5, 199.75, 200.0, 200.25, 200.5].iter().for_each(|&f| {
-        let value = imrd.waveform_single_frequency(f, 0.1);
+        let value = imrd.waveform_single_frequency(f, 0.1, 0.0);
         println!(
             "{:.5}, {:.8e}",
             (value.im.atan2(value.re) - phi_ref + 2.0 * PI) % (2.0 * PI),
@@ -0,0 +1,64 @@
+use std::time::Instant;
+
+use waveformrs::{
+    imrphenomd::IMRPhenomD, multibanding::multibanded_waveform, series::FrequencySeries,
+    waveform::Waveform,
+};
+
+fn main() {
+    let total_mass = 90.0;
+    let mass_ratio = 0.5;
+    let chi_1 = 0.0;
+    let chi_2 = 0.0;
+    let luminosity_distance = 100.0;
+    let reference_frequency = 20.0;
+    let threshold = 1e-3;
+
+    let delta_f: f64 = 0.25;
+    let f_lower: f64 = 20.0;
+    let f_max: f64 = 1500.0;
+    let n_frequencies = ((f_max - f_lower) / delta_f).floor() as usize + 1;
+    let frequencies = (0..n_frequencies)
+        .map(|i| f_lower + i as f64 * delta_f)
+        .collect::<Vec<f64>>();
+
+    let imrd = IMRPhenomD::new(
+        total_mass,
+        mass_ratio,
+        chi_1,
+        chi_2,
+        luminosity_distance,
+        reference_frequency,
+    );
+
+    let start = Instant::now();
+    for _ in 0..1000 {
+        let _: FrequencySeries = imrd.waveform(&frequencies, 0.1, 0.0);
+    }
+    println!("Direct evaluation time: {:?}", start.elapsed() / 1000);
+
+    let start = Instant::now();
+    for _ in 0..1000 {
+        let _: FrequencySeries =
+            multibanded_waveform(&imrd, f_lower, f_max, delta_f, 0.1, 0.0, threshold);
+    }
+    println!("Multibanded evaluation time: {:?}", start.elapsed() / 1000);
+
+    let direct = imrd.waveform(&frequencies, 0.1, 0.0);
+    let multibanded = multibanded_waveform(&imrd, f_lower, f_max, delta_f, 0.1, 0.0, threshold);
+    assert_eq!(direct.len(), multibanded.len());
+    let mut max_phase_error: f64 = 0.0;
+    let mut max_amplitude_error: f64 = 0.0;
+    for (exact, approximate) in direct.data.iter().zip(multibanded.data.iter()) {
+        max_phase_error = max_phase_error.max((approximate / exact).arg().abs());
+        max_amplitude_error =
+            max_amplitude_error.max((approximate.norm() / exact.norm() - 1.0).abs());
+    }
+    println!("Maximum phase error: {:.3e}", max_phase_error);
+    println!(
+        "Maximum relative amplitude error: {:.3e}",
+        max_amplitude_error
+    );
+    assert!(max_phase_error < 2.0 * threshold);
+    assert!(max_amplitude_error < 2.0 * threshold);
+}
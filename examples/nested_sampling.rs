@@ -0,0 +1,182 @@
+use std::time::Instant;
+
+use rand::{rngs::StdRng, SeedableRng};
+use waveformrs::{
+    conversion::{chi_eff, chirp_mass},
+    cosmology::Cosmology,
+    imrphenomd::IMRPhenomD,
+    likelihood::GaussianLikelihood,
+    nested_sampling::NestedSampler,
+    network::Network,
+    parameters::BinaryParameters,
+    prior::{
+        AlignedSpin, BinaryPrior, ComponentMasses, IsotropicOrientation, IsotropicSky, Prior,
+        Uniform, UniformComovingVolume,
+    },
+    psd::PowerSpectralDensity,
+};
+
+// uniform on the square [-5, 5]^2
+struct Square;
+
+impl Prior<2> for Square {
+    fn rescale(&self, unit: [f64; 2]) -> [f64; 2] {
+        unit.map(|value| 10.0 * value - 5.0)
+    }
+
+    fn ln_prob(&self, _: &[f64; 2]) -> f64 {
+        -100.0f64.ln()
+    }
+}
+
+fn quantile(values: &[f64], fraction: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted[((sorted.len() - 1) as f64 * fraction).round() as usize]
+}
+
+fn main() {
+    // a unit normal likelihood with width 0.5 has evidence 1 / 100 under this prior
+    let gaussian = |point: &[f64; 2]| {
+        let width: f64 = 0.5;
+        -(point[0].powi(2) + point[1].powi(2)) / (2.0 * width.powi(2))
+            - (2.0 * std::f64::consts::PI * width.powi(2)).ln()
+    };
+    let sampler = NestedSampler::new(Square, gaussian, 400);
+    let result = sampler.run().unwrap();
+    println!(
+        "gaussian: ln Z = {:.3} +/- {:.3}, expected {:.3}",
+        result.ln_evidence,
+        result.ln_evidence_error,
+        -100.0f64.ln()
+    );
+    assert!((result.ln_evidence + 100.0f64.ln()).abs() < 4.0 * result.ln_evidence_error);
+
+    // a run resumed from a checkpoint reproduces the uninterrupted run
+    let path = std::env::temp_dir().join("waveformrs_nested_sampling_checkpoint.txt");
+    let _ = std::fs::remove_file(&path);
+    let mut checkpointed = NestedSampler::new(Square, gaussian, 400);
+    checkpointed.checkpoint = Some(path.clone());
+    checkpointed.checkpoint_interval = 500;
+    let first = checkpointed.run().unwrap();
+    let resumed = checkpointed.run().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(first.ln_evidence, result.ln_evidence);
+    assert_eq!(resumed.ln_evidence, result.ln_evidence);
+    assert_eq!(resumed.samples, result.samples);
+
+    // IMRPhenomD injection in a two detector network
+    let reference_frequency = 20.0;
+    let model = |p: &BinaryParameters| {
+        IMRPhenomD::new(
+            p.total_mass,
+            p.mass_ratio,
+            p.chi_1,
+            p.chi_2,
+            p.luminosity_distance,
+            reference_frequency,
+        )
+    };
+    let truth = BinaryParameters {
+        total_mass: 65.0,
+        mass_ratio: 0.8,
+        chi_1: 0.3,
+        chi_2: -0.1,
+        ra: 1.375,
+        dec: -1.2108,
+        psi: 0.8,
+        theta_jn: 2.6,
+        phi_c: 0.3,
+        geocent_time: 1126259462.4,
+        luminosity_distance: 1500.0,
+    };
+    let frequencies: Vec<f64> = (0..985).map(|ii| 20.0 + 0.5 * ii as f64).collect();
+    let network = Network::from_names(&["H1", "L1"]).unwrap();
+    let epoch = truth.geocent_time - 1.0;
+    let data = network.strain(&model(&truth), &frequencies, &truth.extrinsic(), epoch);
+    let psds = [
+        PowerSpectralDensity::AdvancedLIGO,
+        PowerSpectralDensity::AdvancedLIGO,
+    ];
+    let likelihood = GaussianLikelihood::new(network, data, &psds, model);
+    println!(
+        "optimal network SNR {:.1}",
+        (2.0 * likelihood.log_likelihood_ratio(&truth)).sqrt()
+    );
+
+    let prior = BinaryPrior {
+        masses: ComponentMasses::new(24.0, 32.0, 0.25),
+        chi_1: AlignedSpin::new(0.99),
+        chi_2: AlignedSpin::new(0.99),
+        sky: IsotropicSky,
+        orientation: IsotropicOrientation,
+        geocent_time: Uniform::new(truth.geocent_time - 0.1, truth.geocent_time + 0.1),
+        luminosity_distance: UniformComovingVolume::new(Cosmology::planck15(), 100.0, 3000.0),
+    };
+    let log_likelihood = |values: &[f64; 11]| {
+        likelihood.log_likelihood_ratio(&BinaryParameters::from_array(*values))
+    };
+    let mut sampler = NestedSampler::new(prior, log_likelihood, 250);
+    // longer walks so the replacement points decorrelate from the survivors they start at
+    sampler.walks = 64;
+    let start = Instant::now();
+    let result = sampler.run().unwrap();
+    println!(
+        "injection: ln Z = {:.2} +/- {:.2}, {} likelihood calls in {:?}",
+        result.ln_evidence,
+        result.ln_evidence_error,
+        result.n_likelihood_evaluations,
+        start.elapsed()
+    );
+
+    let posterior: Vec<BinaryParameters> = result
+        .posterior_samples(&mut StdRng::seed_from_u64(1))
+        .into_iter()
+        .map(BinaryParameters::from_array)
+        .collect();
+    let summaries = [
+        (
+            "chirp mass",
+            chirp_mass(truth.total_mass, truth.mass_ratio),
+            posterior
+                .iter()
+                .map(|p| chirp_mass(p.total_mass, p.mass_ratio))
+                .collect::<Vec<f64>>(),
+        ),
+        (
+            "mass ratio",
+            truth.mass_ratio,
+            posterior.iter().map(|p| p.mass_ratio).collect(),
+        ),
+        (
+            "chi_eff",
+            chi_eff(truth.mass_ratio, truth.chi_1, truth.chi_2),
+            posterior
+                .iter()
+                .map(|p| chi_eff(p.mass_ratio, p.chi_1, p.chi_2))
+                .collect(),
+        ),
+        (
+            "luminosity distance",
+            truth.luminosity_distance,
+            posterior.iter().map(|p| p.luminosity_distance).collect(),
+        ),
+    ];
+    println!("{} posterior samples", posterior.len());
+    for (name, truth, values) in summaries {
+        let (lower, median, upper) = (
+            quantile(&values, 0.05),
+            quantile(&values, 0.5),
+            quantile(&values, 0.95),
+        );
+        println!(
+            "{name:>20}: {median:.3} (+{:.3} -{:.3}), injected {truth:.3}",
+            upper - median,
+            median - lower
+        );
+        assert!(
+            quantile(&values, 0.005) <= truth && truth <= quantile(&values, 0.995),
+            "{name} not recovered"
+        );
+    }
+}
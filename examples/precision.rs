@@ -0,0 +1,114 @@
+use std::time::Instant;
+
+use autodiff::FT;
+use num_complex::Complex;
+use waveformrs::{imrphenomd::IMRPhenomD, taylor::TaylorF2, waveform::Waveform};
+
+fn evaluate<T, W: Waveform<T>>(model: &W, frequencies: &[f64]) -> Vec<Complex<f64>>
+where
+    T: waveformrs::numeric::Real,
+{
+    frequencies
+        .iter()
+        .map(|&f| {
+            let value = model.waveform_single_frequency(
+                T::from_f64(f).unwrap(),
+                T::from_f64(0.1).unwrap(),
+                T::zero(),
+            );
+            Complex::new(value.re.to_f64().unwrap(), value.im.to_f64().unwrap())
+        })
+        .collect()
+}
+
+// largest relative amplitude and absolute phase differences
+fn compare(a: &[Complex<f64>], b: &[Complex<f64>]) -> (f64, f64) {
+    a.iter()
+        .zip(b.iter())
+        .fold((0.0, 0.0), |(amplitude, phase), (x, y)| {
+            (
+                amplitude.max((x.norm() - y.norm()).abs() / x.norm()),
+                phase.max((x / y).arg().abs()),
+            )
+        })
+}
+
+fn time<F: Fn() -> Vec<Complex<f64>>>(function: F) -> std::time::Duration {
+    let start = Instant::now();
+    for _ in 0..100 {
+        function();
+    }
+    start.elapsed() / 100
+}
+
+fn main() {
+    let (total_mass, mass_ratio, chi_1, chi_2) = (60.0, 0.5, 0.3, -0.2);
+    let (luminosity_distance, reference_frequency) = (400.0, 20.0);
+    let frequencies: Vec<f64> = (0..4000).map(|ii| 20.0 + 0.25 * ii as f64).collect();
+
+    let imrd = IMRPhenomD::new(
+        total_mass,
+        mass_ratio,
+        chi_1,
+        chi_2,
+        luminosity_distance,
+        reference_frequency,
+    );
+    let imrd_f32 = IMRPhenomD::<f32>::new(
+        total_mass as f32,
+        mass_ratio as f32,
+        chi_1 as f32,
+        chi_2 as f32,
+        luminosity_distance as f32,
+        reference_frequency,
+    );
+    let imrd_dual = IMRPhenomD::<FT<f64>>::new(
+        FT::var(total_mass),
+        FT::cst(mass_ratio),
+        FT::cst(chi_1),
+        FT::cst(chi_2),
+        FT::cst(luminosity_distance),
+        reference_frequency,
+    );
+    let taylor = TaylorF2::new(
+        total_mass,
+        mass_ratio,
+        chi_1,
+        chi_2,
+        luminosity_distance,
+        reference_frequency,
+    );
+    let taylor_f32 = TaylorF2::<f32>::new(
+        total_mass as f32,
+        mass_ratio as f32,
+        chi_1 as f32,
+        chi_2 as f32,
+        luminosity_distance as f32,
+        reference_frequency,
+    );
+
+    let reference = evaluate(&imrd, &frequencies);
+    let (amplitude, phase) = compare(&reference, &evaluate(&imrd_f32, &frequencies));
+    println!("IMRPhenomD f32: amplitude {amplitude:.2e}, phase {phase:.2e} rad");
+    assert!(amplitude < 1e-3 && phase < 1e-2);
+    let (amplitude, phase) = compare(&reference, &evaluate(&imrd_dual, &frequencies));
+    println!("IMRPhenomD dual: amplitude {amplitude:.2e}, phase {phase:.2e} rad");
+    assert!(amplitude < 1e-14 && phase < 1e-12);
+
+    let reference = evaluate(&taylor, &frequencies);
+    let (amplitude, phase) = compare(&reference, &evaluate(&taylor_f32, &frequencies));
+    println!("TaylorF2 f32: amplitude {amplitude:.2e}, phase {phase:.2e} rad");
+    assert!(amplitude < 1e-3 && phase < 1e-2);
+
+    println!(
+        "IMRPhenomD f64 {:?}, f32 {:?}, dual {:?}",
+        time(|| evaluate(&imrd, &frequencies)),
+        time(|| evaluate(&imrd_f32, &frequencies)),
+        time(|| evaluate(&imrd_dual, &frequencies)),
+    );
+    println!(
+        "TaylorF2 f64 {:?}, f32 {:?}",
+        time(|| evaluate(&taylor, &frequencies)),
+        time(|| evaluate(&taylor_f32, &frequencies)),
+    );
+}
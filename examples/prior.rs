@@ -0,0 +1,89 @@
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+use waveformrs::{
+    cosmology::Cosmology,
+    parameters::BinaryParameters,
+    prior::{
+        AlignedSpin, BinaryPrior, ComponentMasses, IsotropicOrientation, IsotropicSky,
+        IsotropicSpin, Prior, Uniform, UniformComovingVolume,
+    },
+};
+
+fn determinant<const N: usize>(mut matrix: [[f64; N]; N]) -> f64 {
+    let mut value = 1.0;
+    for column in 0..N {
+        let pivot = (column..N)
+            .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))
+            .unwrap();
+        if pivot != column {
+            matrix.swap(column, pivot);
+            value = -value;
+        }
+        value *= matrix[column][column];
+        let pivot_row = matrix[column];
+        for row in matrix.iter_mut().skip(column + 1) {
+            let factor = row[column] / pivot_row[column];
+            for (entry, &pivot_entry) in row.iter_mut().zip(pivot_row.iter()).skip(column) {
+                *entry -= factor * pivot_entry;
+            }
+        }
+    }
+    value
+}
+
+// the density must be the inverse of the volume change of the unit cube transform
+fn check<P: Prior<N>, const N: usize, R: RngExt>(name: &str, prior: &P, rng: &mut R) {
+    let step = 1e-6;
+    let mut worst: f64 = 0.0;
+    for _ in 0..200 {
+        let unit: [f64; N] = std::array::from_fn(|_| 0.01 + 0.98 * rng.random::<f64>());
+        let columns: [[f64; N]; N] = std::array::from_fn(|jj| {
+            let shifted = |offset: f64| {
+                let mut point = unit;
+                point[jj] += offset;
+                prior.rescale(point)
+            };
+            let (upper, lower) = (shifted(step), shifted(-step));
+            std::array::from_fn(|ii| (upper[ii] - lower[ii]) / (2.0 * step))
+        });
+        let volume = determinant(columns).abs();
+        let density = prior.ln_prob(&prior.rescale(unit)).exp();
+        worst = worst.max((density * volume - 1.0).abs());
+    }
+    println!("{name}: maximum error in density times volume {worst:.2e}");
+    assert!(worst < 1e-4, "{name} transform and density disagree");
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(150914);
+    let masses = ComponentMasses::new(20.0, 40.0, 0.125);
+    let prior = BinaryPrior {
+        masses: masses.clone(),
+        chi_1: AlignedSpin::new(0.99),
+        chi_2: AlignedSpin::new(0.99),
+        sky: IsotropicSky,
+        orientation: IsotropicOrientation,
+        geocent_time: Uniform::new(1126259462.3, 1126259462.5),
+        luminosity_distance: UniformComovingVolume::new(Cosmology::planck15(), 100.0, 5000.0),
+    };
+    check("component masses", &masses, &mut rng);
+    check("aligned spin", &prior.chi_1, &mut rng);
+    check("isotropic spin", &IsotropicSpin::new(0.99), &mut rng);
+    check("distance", &prior.luminosity_distance, &mut rng);
+    check("sky", &IsotropicSky, &mut rng);
+    check("orientation", &IsotropicOrientation, &mut rng);
+
+    // the aligned projection of isotropic spins follows the aligned spin prior
+    let isotropic = IsotropicSpin::new(0.99);
+    let n_samples = 200000;
+    let count = |sample: &mut dyn FnMut() -> f64| {
+        (0..n_samples).filter(|_| sample().abs() < 0.1).count() as f64 / n_samples as f64
+    };
+    let projected = count(&mut || IsotropicSpin::aligned_component(&isotropic.sample(&mut rng)));
+    let aligned = count(&mut || prior.chi_1.sample(&mut rng)[0]);
+    println!("fraction with |chi| < 0.1: projected {projected:.4}, aligned {aligned:.4}");
+    assert!((projected - aligned).abs() < 0.01);
+
+    let sample = BinaryParameters::from_array(prior.sample(&mut rng));
+    println!("{sample:?}");
+    println!("ln prior {:.4}", prior.ln_prob(&sample.to_array()));
+}
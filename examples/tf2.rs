@@ -1,7 +1,6 @@
-use num_complex::Complex;
 use std::time::Instant;
 
-use waveformrs::{taylor::TaylorF2, waveform::Waveform};
+use waveformrs::{series::FrequencySeries, taylor::TaylorF2, waveform::Waveform};
 
 fn main() {
     let total_mass = 90.0;
@@ -9,6 +8,7 @@ fn main() {
     let chi_1 = 0.0;
     let chi_2 = 0.0;
     let luminosity_distance = 100.0;
+    let reference_frequency = 20.0;
 
     let delta_f: f64 = 0.25;
     let f_lower: f64 = 20.0;
@@ -19,18 +19,17 @@ fn main() {
     for _ in 0..1000 {
         let frequencies = (0..n_frequencies)
             .map(|i| i as f64 * delta_f)
+            .filter(|&f| f_lower <= f && f <= f_max)
             .collect::<Vec<f64>>();
-        let taylor_f2 = TaylorF2::new(total_mass, mass_ratio, chi_1, chi_2, luminosity_distance);
-        let _: Vec<Complex<f64>> = frequencies
-            .iter()
-            .map(|&f| {
-                if f < f_lower || f > f_max {
-                    return Complex::<f64>::new(0.0, 0.0);
-                } else {
-                    taylor_f2.waveform_single_frequency(f, 0.1)
-                }
-            })
-            .collect();
+        let taylor_f2 = TaylorF2::new(
+            total_mass,
+            mass_ratio,
+            chi_1,
+            chi_2,
+            luminosity_distance,
+            reference_frequency,
+        );
+        let _: FrequencySeries = taylor_f2.waveform(&frequencies, 0.1, 0.0);
     }
     let duration = start.elapsed();
     println!("Average evaluation time: {:?}", duration / 1000);
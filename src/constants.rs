@@ -5,3 +5,5 @@ pub const GMSUN_SI: f64 = 1.3271244e20;
 pub const SOLAR_RADIUS_IN_M: f64 = GMSUN_SI / SPEED_OF_LIGHT_IN_VACUUM / SPEED_OF_LIGHT_IN_VACUUM;
 pub const SOLAR_RADIUS_IN_S: f64 = SOLAR_RADIUS_IN_M / SPEED_OF_LIGHT_IN_VACUUM;
 pub const EGAMMA: f64 = 0.577215664901532860606512090082402431;
+pub const WGS84_SEMI_MAJOR_AXIS: f64 = 6378137.0;
+pub const WGS84_SEMI_MINOR_AXIS: f64 = 6356752.314;
@@ -0,0 +1,188 @@
+use crate::{
+    numeric::{cast, Real},
+    pn::{self, Params},
+};
+
+// Masses are in any common unit. `mass_ratio` is q = m2 / m1 <= 1 as in `Params` and
+// `BinaryParameters`, `inverse_mass_ratio` is 1 / q = m1 / m2 >= 1. Spins are the
+// dimensionless components along the orbital angular momentum unless stated otherwise.
+
+/// Switch between the q <= 1 and q >= 1 conventions, the map is its own inverse.
+pub fn inverse_mass_ratio<T: Real>(mass_ratio: T) -> T {
+    T::one() / mass_ratio
+}
+
+pub fn symmetric_mass_ratio<T: Real>(mass_ratio: T) -> T {
+    mass_ratio / (T::one() + mass_ratio).powi(2)
+}
+
+/// The mass ratio q <= 1 for a symmetric mass ratio eta <= 1/4.
+pub fn mass_ratio_from_symmetric_mass_ratio<T: Real>(eta: T) -> T {
+    let c = cast::<T>;
+    let root = (T::one() - c(4.0) * eta).sqrt();
+    (T::one() - c(2.0) * eta - root) / (c(2.0) * eta)
+}
+
+pub fn chirp_mass<T: Real>(total_mass: T, mass_ratio: T) -> T {
+    total_mass * symmetric_mass_ratio(mass_ratio).powf(cast::<T>(3.0) / cast::<T>(5.0))
+}
+
+pub fn total_mass_from_chirp_mass<T: Real>(chirp_mass: T, mass_ratio: T) -> T {
+    chirp_mass * symmetric_mass_ratio(mass_ratio).powf(cast::<T>(-3.0) / cast::<T>(5.0))
+}
+
+/// (m1, m2) with m1 >= m2.
+pub fn component_masses<T: Real>(total_mass: T, mass_ratio: T) -> (T, T) {
+    let mass_1 = total_mass / (T::one() + mass_ratio);
+    (mass_1, mass_1 * mass_ratio)
+}
+
+pub fn component_masses_from_chirp_mass<T: Real>(chirp_mass: T, mass_ratio: T) -> (T, T) {
+    component_masses(
+        total_mass_from_chirp_mass(chirp_mass, mass_ratio),
+        mass_ratio,
+    )
+}
+
+/// (total mass, mass ratio), the mass ratio is the lighter mass over the heavier so q <= 1
+/// in either order. Swap the spins too if `mass_2` is the heavier.
+pub fn total_mass_and_mass_ratio<T: Real>(mass_1: T, mass_2: T) -> (T, T) {
+    (mass_1 + mass_2, mass_1.min(mass_2) / mass_1.max(mass_2))
+}
+
+/// Mass-weighted aligned spin.
+pub fn chi_eff<T: Real>(mass_ratio: T, chi_1: T, chi_2: T) -> T {
+    (chi_1 + mass_ratio * chi_2) / (T::one() + mass_ratio)
+}
+
+/// Antisymmetric spin (chi_1 - chi_2) / 2.
+pub fn chi_a<T: Real>(chi_1: T, chi_2: T) -> T {
+    (chi_1 - chi_2) / cast(2.0)
+}
+
+/// (chi_1, chi_2) from `chi_eff` and `chi_a`, the spins may exceed one in magnitude.
+pub fn component_spins<T: Real>(mass_ratio: T, chi_eff: T, chi_a: T) -> (T, T) {
+    let scale = cast::<T>(2.0) * chi_a / (T::one() + mass_ratio);
+    (chi_eff + mass_ratio * scale, chi_eff - scale)
+}
+
+/// Reduced spin that the IMRPhenomD fits are expressed in.
+pub fn chi_pn<T: Real>(mass_ratio: T, chi_1: T, chi_2: T) -> T {
+    pn::chi_pn(&Params::new(mass_ratio, chi_1, chi_2))
+}
+
+/// Effective precession spin from the magnitudes of the in-plane spin components,
+/// Schmidt, Ohme and Hannam, Phys. Rev. D 91, 024043 (2015).
+pub fn chi_p<T: Real>(mass_ratio: T, chi_1_perp: T, chi_2_perp: T) -> T {
+    let c = cast::<T>;
+    let weight = mass_ratio * (c(4.0) * mass_ratio + c(3.0)) / (c(4.0) + c(3.0) * mass_ratio);
+    chi_1_perp.abs().max(weight * chi_2_perp.abs())
+}
+
+// The Jacobians are |d(old) / d(new)| so a density in the old coordinates converts as
+// p(new) = p(old) * jacobian.
+
+/// |d(m1, m2) / d(chirp mass, q)| = m1^2 / chirp mass.
+pub fn component_masses_to_chirp_mass_jacobian<T: Real>(chirp_mass: T, mass_ratio: T) -> T {
+    let (mass_1, _) = component_masses_from_chirp_mass(chirp_mass, mass_ratio);
+    mass_1.powi(2) / chirp_mass
+}
+
+/// |d(m1, m2) / d(total mass, q)| = M / (1 + q)^2.
+pub fn component_masses_to_total_mass_jacobian<T: Real>(total_mass: T, mass_ratio: T) -> T {
+    total_mass / (T::one() + mass_ratio).powi(2)
+}
+
+/// |d(chirp mass, q) / d(chirp mass, eta)| = 1 / |d eta / dq|.
+pub fn mass_ratio_to_symmetric_mass_ratio_jacobian<T: Real>(mass_ratio: T) -> T {
+    ((T::one() + mass_ratio).powi(3) / (T::one() - mass_ratio)).abs()
+}
+
+/// |d(chi_1, chi_2) / d(chi_eff, chi_a)| at fixed mass ratio.
+pub const COMPONENT_SPINS_TO_CHI_EFF_JACOBIAN: f64 = 2.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-12 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    // |det| of the 2 x 2 Jacobian of `map` by central differences.
+    fn numerical_jacobian<F: Fn(f64, f64) -> (f64, f64)>(map: F, x: f64, y: f64) -> f64 {
+        let step = 1e-6;
+        let derivative = |dx: f64, dy: f64| {
+            let (upper, lower) = (map(x + dx, y + dy), map(x - dx, y - dy));
+            (
+                (upper.0 - lower.0) / (2.0 * step),
+                (upper.1 - lower.1) / (2.0 * step),
+            )
+        };
+        let (a, c) = derivative(step * x, 0.0);
+        let (b, d) = derivative(0.0, step * y);
+        ((a * d - b * c) / (x * y)).abs()
+    }
+
+    #[test]
+    fn mass_conversions_round_trip() {
+        for mass_ratio in [0.1, 0.5, 0.9] {
+            let (mass_1, mass_2) = component_masses(40.0, mass_ratio);
+            assert!(mass_1 >= mass_2);
+            for (first, second) in [(mass_1, mass_2), (mass_2, mass_1)] {
+                let (total_mass, recovered) = total_mass_and_mass_ratio(first, second);
+                assert!(close(total_mass, 40.0) && close(recovered, mass_ratio));
+            }
+            let eta = symmetric_mass_ratio(mass_ratio);
+            assert!(close(mass_ratio_from_symmetric_mass_ratio(eta), mass_ratio));
+            assert!(close(
+                inverse_mass_ratio(inverse_mass_ratio(mass_ratio)),
+                mass_ratio
+            ));
+            let chirp = chirp_mass(40.0, mass_ratio);
+            assert!(close(total_mass_from_chirp_mass(chirp, mass_ratio), 40.0));
+            let (m1, m2) = component_masses_from_chirp_mass(chirp, mass_ratio);
+            assert!(close(m1, mass_1) && close(m2, mass_2));
+            assert!(close((m1 * m2).powf(0.6) / (m1 + m2).powf(0.2), chirp));
+        }
+    }
+
+    #[test]
+    fn spin_conversions_round_trip() {
+        let (mass_ratio, chi_1, chi_2) = (0.6, 0.4, -0.3);
+        let (effective, antisymmetric) = (chi_eff(mass_ratio, chi_1, chi_2), chi_a(chi_1, chi_2));
+        let (recovered_1, recovered_2) = component_spins(mass_ratio, effective, antisymmetric);
+        assert!(close(recovered_1, chi_1) && close(recovered_2, chi_2));
+        // equal spins give an equal effective spin and a reduced spin that is at most as
+        // large
+        assert!(close(chi_eff(mass_ratio, 0.5, 0.5), 0.5));
+        assert!(chi_pn(mass_ratio, 0.5, 0.5) <= 0.5);
+        assert_eq!(chi_p(mass_ratio, 0.2, 0.0), 0.2);
+    }
+
+    #[test]
+    fn jacobians_match_numerical_determinants() {
+        let (chirp, mass_ratio) = (25.0, 0.6);
+        let expected = numerical_jacobian(component_masses_from_chirp_mass, chirp, mass_ratio);
+        let value = component_masses_to_chirp_mass_jacobian(chirp, mass_ratio);
+        assert!((value / expected - 1.0).abs() < 1e-7);
+
+        let expected = numerical_jacobian(component_masses, 60.0, mass_ratio);
+        let value = component_masses_to_total_mass_jacobian(60.0, mass_ratio);
+        assert!((value / expected - 1.0).abs() < 1e-7);
+
+        // (chirp mass, eta) to (chirp mass, q)
+        let to_mass_ratio =
+            |chirp: f64, eta: f64| (chirp, mass_ratio_from_symmetric_mass_ratio(eta));
+        let eta = symmetric_mass_ratio(mass_ratio);
+        let expected = numerical_jacobian(to_mass_ratio, chirp, eta);
+        let value = mass_ratio_to_symmetric_mass_ratio_jacobian(mass_ratio);
+        assert!((value / expected - 1.0).abs() < 1e-7);
+
+        let to_spins = |effective: f64, antisymmetric: f64| {
+            component_spins(mass_ratio, effective, antisymmetric)
+        };
+        let expected = numerical_jacobian(to_spins, 0.2, 0.1);
+        assert!((COMPONENT_SPINS_TO_CHI_EFF_JACOBIAN / expected - 1.0).abs() < 1e-7);
+    }
+}
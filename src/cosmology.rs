@@ -0,0 +1,189 @@
+use crate::{
+    constants::SPEED_OF_LIGHT_IN_VACUUM,
+    numeric::{cast, Real},
+};
+
+// intervals of the Simpson rule for the comoving distance, the integrand is smooth
+const INTEGRATION_INTERVALS: usize = 512;
+
+/// Flat ΛCDM cosmology, radiation is neglected.
+///
+/// Distances are in Mpc and volumes in Mpc^3. The methods are generic so derivatives with
+/// respect to the redshift or distance carry through with dual numbers.
+#[derive(Clone, Copy, Debug)]
+pub struct Cosmology {
+    /// km / s / Mpc
+    pub hubble_constant: f64,
+    pub omega_matter: f64,
+}
+
+impl Cosmology {
+    pub fn new(hubble_constant: f64, omega_matter: f64) -> Self {
+        Self {
+            hubble_constant,
+            omega_matter,
+        }
+    }
+
+    /// Planck 2015 TT,TE,EE+lowP+lensing+ext, Table 4 of arXiv:1502.01589.
+    pub fn planck15() -> Self {
+        Self::new(67.74, 0.3075)
+    }
+
+    /// Planck 2018 TT,TE,EE+lowE+lensing+BAO, Table 2 of arXiv:1807.06209.
+    pub fn planck18() -> Self {
+        Self::new(67.66, 0.30966)
+    }
+
+    /// c / H0
+    pub fn hubble_distance(&self) -> f64 {
+        SPEED_OF_LIGHT_IN_VACUUM / 1000.0 / self.hubble_constant
+    }
+
+    // H(z) / H0
+    pub(crate) fn efunc<T: Real>(&self, redshift: T) -> T {
+        let omega_matter = cast::<T>(self.omega_matter);
+        (omega_matter * (T::one() + redshift).powi(3) + T::one() - omega_matter).sqrt()
+    }
+
+    pub fn comoving_distance<T: Real>(&self, redshift: T) -> T {
+        let step = redshift / cast(INTEGRATION_INTERVALS as f64);
+        let mut integral = T::one() / self.efunc(T::zero()) + T::one() / self.efunc(redshift);
+        for ii in 1..INTEGRATION_INTERVALS {
+            let weight = cast::<T>(if ii % 2 == 1 { 4.0 } else { 2.0 });
+            integral += weight / self.efunc(step * cast(ii as f64));
+        }
+        integral * step / cast(3.0) * cast(self.hubble_distance())
+    }
+
+    pub fn luminosity_distance<T: Real>(&self, redshift: T) -> T {
+        (T::one() + redshift) * self.comoving_distance(redshift)
+    }
+
+    /// d luminosity distance / d redshift
+    pub fn luminosity_distance_derivative<T: Real>(&self, redshift: T) -> T {
+        self.comoving_distance(redshift)
+            + (T::one() + redshift) * cast(self.hubble_distance()) / self.efunc(redshift)
+    }
+
+    /// Redshift at a luminosity distance in Mpc.
+    pub fn redshift<T: Real>(&self, luminosity_distance: T) -> T {
+        newton(
+            luminosity_distance / cast(self.hubble_distance()),
+            |redshift| {
+                (
+                    self.luminosity_distance(redshift) - luminosity_distance,
+                    self.luminosity_distance_derivative(redshift),
+                )
+            },
+        )
+    }
+
+    /// Redshift at a comoving distance in Mpc.
+    pub fn redshift_at_comoving_distance<T: Real>(&self, comoving_distance: T) -> T {
+        newton(
+            comoving_distance / cast(self.hubble_distance()),
+            |redshift| {
+                (
+                    self.comoving_distance(redshift) - comoving_distance,
+                    cast::<T>(self.hubble_distance()) / self.efunc(redshift),
+                )
+            },
+        )
+    }
+
+    /// Comoving volume of the whole sky out to `redshift`.
+    pub fn comoving_volume<T: Real>(&self, redshift: T) -> T {
+        let c = cast::<T>;
+        (c(4.0) / c(3.0)) * T::PI() * self.comoving_distance(redshift).powi(3)
+    }
+
+    /// d comoving volume / d redshift for the whole sky.
+    pub fn differential_comoving_volume<T: Real>(&self, redshift: T) -> T {
+        cast::<T>(4.0)
+            * T::PI()
+            * cast(self.hubble_distance())
+            * self.comoving_distance(redshift).powi(2)
+            / self.efunc(redshift)
+    }
+}
+
+// Root of a monotonic distance-redshift relation starting from the Hubble law, `residual`
+// returns the offset from the target distance and its derivative.
+fn newton<T: Real, F: Fn(T) -> (T, T)>(mut redshift: T, residual: F) -> T {
+    for _ in 0..100 {
+        let (offset, derivative) = residual(redshift);
+        let step = offset / derivative;
+        redshift -= step;
+        if step.abs() <= cast::<T>(4.0) * T::epsilon() * (T::one() + redshift) {
+            break;
+        }
+    }
+    redshift
+}
+
+/// Redshifted mass m (1 + z) seen in the detector.
+pub fn detector_frame_mass<T: Real>(source_frame_mass: T, redshift: T) -> T {
+    source_frame_mass * (T::one() + redshift)
+}
+
+pub fn source_frame_mass<T: Real>(detector_frame_mass: T, redshift: T) -> T {
+    detector_frame_mass / (T::one() + redshift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn einstein_de_sitter_comoving_distance() {
+        // with only matter the comoving distance is 2 c / H0 (1 - 1 / sqrt(1 + z))
+        let cosmology = Cosmology::new(70.0, 1.0);
+        for redshift in [0.1_f64, 1.0, 5.0] {
+            let expected =
+                2.0 * cosmology.hubble_distance() * (1.0 - 1.0 / (1.0 + redshift).sqrt());
+            let value = cosmology.comoving_distance(redshift);
+            assert!((value / expected - 1.0).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn redshift_inverts_the_distances() {
+        let cosmology = Cosmology::planck18();
+        for redshift in [1e-4_f64, 0.05, 0.5, 2.0, 10.0] {
+            let distance = cosmology.luminosity_distance(redshift);
+            assert!((cosmology.redshift(distance) / redshift - 1.0).abs() < 1e-12);
+            let distance = cosmology.comoving_distance(redshift);
+            let recovered = cosmology.redshift_at_comoving_distance(distance);
+            assert!((recovered / redshift - 1.0).abs() < 1e-12);
+        }
+        // Hubble law at low redshift
+        let redshift = cosmology.redshift(1.0_f64);
+        assert!((redshift * cosmology.hubble_distance() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn derivatives_match_finite_differences() {
+        let cosmology = Cosmology::planck15();
+        let step = 1e-5;
+        for redshift in [0.1_f64, 1.0, 3.0] {
+            let numerical = (cosmology.luminosity_distance(redshift + step)
+                - cosmology.luminosity_distance(redshift - step))
+                / (2.0 * step);
+            let value = cosmology.luminosity_distance_derivative(redshift);
+            assert!((value / numerical - 1.0).abs() < 1e-7);
+            let numerical = (cosmology.comoving_volume(redshift + step)
+                - cosmology.comoving_volume(redshift - step))
+                / (2.0 * step);
+            let value = cosmology.differential_comoving_volume(redshift);
+            assert!((value / numerical - 1.0).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn masses_round_trip() {
+        let mass = detector_frame_mass(30.0_f64, 0.4);
+        assert!((mass - 42.0).abs() < 1e-12);
+        assert!((source_frame_mass(mass, 0.4) - 30.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,361 @@
+use std::f64::consts::PI;
+
+use num_complex::Complex;
+
+use crate::{
+    constants::{WGS84_SEMI_MAJOR_AXIS, WGS84_SEMI_MINOR_AXIS},
+    numeric::{cast, Real},
+    parameters::ExtrinsicParameters,
+    series::FrequencySeries,
+    time::{
+        greenwich_mean_sidereal_time, time_delay_at_sidereal_time, time_delay_from_geocenter,
+        SIDEREAL_RATE,
+    },
+};
+
+/// A ground-based interferometer.
+///
+/// Angles are in radians, arm azimuths are measured from East towards North and arm
+/// tilts are the altitude of the arm above the local horizontal.
+#[derive(Clone, Debug)]
+pub struct Detector {
+    pub name: String,
+    latitude: f64,
+    longitude: f64,
+    elevation: f64,
+    vertex: [f64; 3],
+    x_arm: [f64; 3],
+    y_arm: [f64; 3],
+    detector_tensor: [[f64; 3]; 3],
+}
+
+impl Detector {
+    pub fn new(
+        name: &str,
+        latitude: f64,
+        longitude: f64,
+        elevation: f64,
+        xarm_azimuth: f64,
+        yarm_azimuth: f64,
+    ) -> Self {
+        let mut new = Self {
+            name: name.to_string(),
+            latitude,
+            longitude,
+            elevation,
+            vertex: vertex_position(latitude, longitude, elevation),
+            x_arm: [0.0; 3],
+            y_arm: [0.0; 3],
+            detector_tensor: [[0.0; 3]; 3],
+        };
+        new.set_arms(xarm_azimuth, yarm_azimuth, 0.0, 0.0);
+        new
+    }
+
+    pub fn with_arm_tilts(mut self, xarm_tilt: f64, yarm_tilt: f64) -> Self {
+        let azimuth = |arm: [f64; 3]| {
+            let (east, north) = self.local_components(arm);
+            north.atan2(east)
+        };
+        self.set_arms(
+            azimuth(self.x_arm),
+            azimuth(self.y_arm),
+            xarm_tilt,
+            yarm_tilt,
+        );
+        self
+    }
+
+    pub fn hanford() -> Self {
+        Self::new(
+            "H1",
+            dms(46.0, 27.0, 18.528),
+            -dms(119.0, 24.0, 27.5657),
+            142.554,
+            125.9994f64.to_radians(),
+            215.9994f64.to_radians(),
+        )
+        .with_arm_tilts(-6.195e-4, 1.25e-5)
+    }
+
+    pub fn livingston() -> Self {
+        Self::new(
+            "L1",
+            dms(30.0, 33.0, 46.4196),
+            -dms(90.0, 46.0, 27.2654),
+            -6.574,
+            197.7165f64.to_radians(),
+            287.7165f64.to_radians(),
+        )
+        .with_arm_tilts(-3.121e-4, -6.107e-4)
+    }
+
+    pub fn virgo() -> Self {
+        Self::new(
+            "V1",
+            dms(43.0, 37.0, 53.0921),
+            dms(10.0, 30.0, 16.1878),
+            51.884,
+            70.5674f64.to_radians(),
+            160.5674f64.to_radians(),
+        )
+    }
+
+    pub fn kagra() -> Self {
+        Self::new(
+            "K1",
+            dms(36.0, 24.0, 42.69),
+            dms(137.0, 18.0, 21.44),
+            414.181,
+            (90.0 - 60.39623489157727f64).to_radians(),
+            (90.0 + 29.60376510842273f64).to_radians(),
+        )
+        .with_arm_tilts(0.0031414, -0.0036270)
+    }
+
+    pub fn ligo_india() -> Self {
+        Self::new(
+            "A1",
+            dms(19.0, 36.0, 47.9017),
+            dms(77.0, 1.0, 51.0997),
+            440.0,
+            117.6157f64.to_radians(),
+            207.6165f64.to_radians(),
+        )
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "H1" => Some(Self::hanford()),
+            "L1" => Some(Self::livingston()),
+            "V1" => Some(Self::virgo()),
+            "K1" => Some(Self::kagra()),
+            "A1" => Some(Self::ligo_india()),
+            _ => None,
+        }
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    pub fn elevation(&self) -> f64 {
+        self.elevation
+    }
+
+    /// Earth-fixed position of the vertex in metres.
+    pub fn vertex(&self) -> [f64; 3] {
+        self.vertex
+    }
+
+    pub fn detector_tensor(&self) -> [[f64; 3]; 3] {
+        self.detector_tensor
+    }
+
+    pub fn antenna_pattern(&self, ra: f64, dec: f64, psi: f64, gps_time: f64) -> (f64, f64) {
+        let gmst = greenwich_mean_sidereal_time(gps_time);
+        self.antenna_pattern_at_sidereal_time(ra, dec, psi, gmst)
+    }
+
+    pub fn antenna_pattern_at_sidereal_time<T: Real>(
+        &self,
+        ra: T,
+        dec: T,
+        psi: T,
+        gmst: T,
+    ) -> (T, T) {
+        let (plus, cross) = polarization_tensors(ra, dec, psi, gmst);
+        (
+            contract(&self.detector_tensor, &plus),
+            contract(&self.detector_tensor, &cross),
+        )
+    }
+
+    pub fn time_delay_from_geocenter(&self, ra: f64, dec: f64, gps_time: f64) -> f64 {
+        time_delay_from_geocenter(&self.vertex, ra, dec, gps_time)
+    }
+
+    /// Antenna pattern and time delay from the geocentre for any scalar type, with dual
+    /// numbers the sidereal time follows `geocent_time` at the mean sidereal rate.
+    pub fn response<T: Real>(&self, ra: T, dec: T, psi: T, geocent_time: T) -> (T, T, T) {
+        let gps_time = geocent_time.to_f64().unwrap();
+        let gmst = cast::<T>(greenwich_mean_sidereal_time(gps_time))
+            + (geocent_time - cast(gps_time)) * cast(SIDEREAL_RATE);
+        let (f_plus, f_cross) = self.antenna_pattern_at_sidereal_time(ra, dec, psi, gmst);
+        let delay = time_delay_at_sidereal_time(&self.vertex, ra, dec, gmst);
+        (f_plus, f_cross, delay)
+    }
+
+    pub fn time_delay_from_detector(
+        &self,
+        other: &Detector,
+        ra: f64,
+        dec: f64,
+        gps_time: f64,
+    ) -> f64 {
+        self.time_delay_from_geocenter(ra, dec, gps_time)
+            - other.time_delay_from_geocenter(ra, dec, gps_time)
+    }
+
+    /// Delay a geocentric waveform by the light travel time to this detector.
+    pub fn shift_to_detector(
+        &self,
+        strain: &FrequencySeries,
+        ra: f64,
+        dec: f64,
+        gps_time: f64,
+    ) -> FrequencySeries {
+        let delay = self.time_delay_from_geocenter(ra, dec, gps_time);
+        let mut shifted = strain.clone();
+        for (value, &frequency) in shifted.data.iter_mut().zip(strain.frequencies().iter()) {
+            *value *= Complex::new(0.0, -2.0 * PI * frequency * delay).exp();
+        }
+        shifted
+    }
+
+    /// Response to geocentric polarisations with t = 0 at the geocentric coalescence,
+    /// the Fourier phases of the result are referenced to `epoch`.
+    pub fn project(
+        &self,
+        plus: &FrequencySeries,
+        cross: &FrequencySeries,
+        parameters: &ExtrinsicParameters,
+        epoch: f64,
+    ) -> FrequencySeries {
+        let mut strain = FrequencySeries::zeros(plus.frequencies().to_vec(), epoch, plus.units);
+        self.project_into(plus, cross, parameters, epoch, &mut strain);
+        strain
+    }
+
+    /// `project` written in place, `strain` must have the frequencies of the polarisations.
+    pub fn project_into(
+        &self,
+        plus: &FrequencySeries,
+        cross: &FrequencySeries,
+        parameters: &ExtrinsicParameters,
+        epoch: f64,
+        strain: &mut FrequencySeries,
+    ) {
+        assert!(
+            plus.len() == cross.len() && plus.len() == strain.len(),
+            "polarisations and strain have different lengths"
+        );
+        let (f_plus, f_cross) = self.antenna_pattern(
+            parameters.ra,
+            parameters.dec,
+            parameters.psi,
+            parameters.geocent_time,
+        );
+        let delay = parameters.geocent_time - epoch
+            + self.time_delay_from_geocenter(
+                parameters.ra,
+                parameters.dec,
+                parameters.geocent_time,
+            );
+        for (((value, &h_plus), &h_cross), &frequency) in strain
+            .data
+            .iter_mut()
+            .zip(plus.data.iter())
+            .zip(cross.data.iter())
+            .zip(plus.frequencies().iter())
+        {
+            *value = (f_plus * h_plus + f_cross * h_cross)
+                * Complex::new(0.0, -2.0 * PI * frequency * delay).exp();
+        }
+        strain.epoch = epoch;
+        strain.units = plus.units;
+    }
+
+    fn local_components(&self, arm: [f64; 3]) -> (f64, f64) {
+        let (east, north, _) = local_basis(self.latitude, self.longitude);
+        (dot(&arm, &east), dot(&arm, &north))
+    }
+
+    fn set_arms(&mut self, xarm_azimuth: f64, yarm_azimuth: f64, xarm_tilt: f64, yarm_tilt: f64) {
+        self.x_arm = arm_direction(self.latitude, self.longitude, xarm_azimuth, xarm_tilt);
+        self.y_arm = arm_direction(self.latitude, self.longitude, yarm_azimuth, yarm_tilt);
+        for ii in 0..3 {
+            for jj in 0..3 {
+                self.detector_tensor[ii][jj] =
+                    0.5 * (self.x_arm[ii] * self.x_arm[jj] - self.y_arm[ii] * self.y_arm[jj]);
+            }
+        }
+    }
+}
+
+fn dms(degrees: f64, minutes: f64, seconds: f64) -> f64 {
+    (degrees + minutes / 60.0 + seconds / 3600.0).to_radians()
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn contract<T: Real>(a: &[[f64; 3]; 3], b: &[[T; 3]; 3]) -> T {
+    a.iter()
+        .flatten()
+        .zip(b.iter().flatten())
+        .map(|(&x, &y)| cast::<T>(x) * y)
+        .sum()
+}
+
+fn local_basis(latitude: f64, longitude: f64) -> ([f64; 3], [f64; 3], [f64; 3]) {
+    let (sin_lat, cos_lat) = latitude.sin_cos();
+    let (sin_lon, cos_lon) = longitude.sin_cos();
+    let east = [-sin_lon, cos_lon, 0.0];
+    let north = [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat];
+    let up = [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat];
+    (east, north, up)
+}
+
+fn arm_direction(latitude: f64, longitude: f64, azimuth: f64, tilt: f64) -> [f64; 3] {
+    let (east, north, up) = local_basis(latitude, longitude);
+    let mut arm = [0.0; 3];
+    for ii in 0..3 {
+        arm[ii] = tilt.cos() * azimuth.cos() * east[ii]
+            + tilt.cos() * azimuth.sin() * north[ii]
+            + tilt.sin() * up[ii];
+    }
+    arm
+}
+
+fn vertex_position(latitude: f64, longitude: f64, elevation: f64) -> [f64; 3] {
+    let a2 = WGS84_SEMI_MAJOR_AXIS.powi(2);
+    let b2 = WGS84_SEMI_MINOR_AXIS.powi(2);
+    let radius = a2 / (a2 * latitude.cos().powi(2) + b2 * latitude.sin().powi(2)).sqrt();
+    [
+        (radius + elevation) * latitude.cos() * longitude.cos(),
+        (radius + elevation) * latitude.cos() * longitude.sin(),
+        (b2 / a2 * radius + elevation) * latitude.sin(),
+    ]
+}
+
+fn polarization_tensors<T: Real>(ra: T, dec: T, psi: T, gmst: T) -> ([[T; 3]; 3], [[T; 3]; 3]) {
+    let phi = ra - gmst;
+    let theta = T::FRAC_PI_2() - dec;
+    let u = [
+        phi.cos() * theta.cos(),
+        theta.cos() * phi.sin(),
+        -theta.sin(),
+    ];
+    let v = [-phi.sin(), phi.cos(), T::zero()];
+    let mut m = [T::zero(); 3];
+    let mut n = [T::zero(); 3];
+    for ii in 0..3 {
+        m[ii] = -u[ii] * psi.sin() - v[ii] * psi.cos();
+        n[ii] = -u[ii] * psi.cos() + v[ii] * psi.sin();
+    }
+    let mut plus = [[T::zero(); 3]; 3];
+    let mut cross = [[T::zero(); 3]; 3];
+    for ii in 0..3 {
+        for jj in 0..3 {
+            plus[ii][jj] = m[ii] * m[jj] - n[ii] * n[jj];
+            cross[ii][jj] = m[ii] * n[jj] + n[ii] * m[jj];
+        }
+    }
+    (plus, cross)
+}
@@ -0,0 +1,36 @@
+use num_complex::Complex;
+use rustfft::FftPlanner;
+
+// The transforms are unnormalised, multiply by delta_t (forward) or delta_f (inverse)
+// to approximate the continuous Fourier transform.
+
+pub fn rfft(data: &[f64]) -> Vec<Complex<f64>> {
+    let mut buffer: Vec<Complex<f64>> = data.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    FftPlanner::new()
+        .plan_fft_forward(buffer.len())
+        .process(&mut buffer);
+    buffer.truncate(data.len() / 2 + 1);
+    buffer
+}
+
+pub fn irfft(data: &[Complex<f64>], length: usize) -> Vec<f64> {
+    let mut buffer = vec![Complex::new(0.0, 0.0); length];
+    for (ii, &value) in data.iter().enumerate().take(length / 2 + 1) {
+        buffer[ii] = value;
+        if ii > 0 && length - ii > ii {
+            buffer[length - ii] = value.conj();
+        }
+    }
+    FftPlanner::new()
+        .plan_fft_inverse(length)
+        .process(&mut buffer);
+    buffer.iter().map(|value| value.re).collect()
+}
+
+pub fn ifft(data: &[Complex<f64>]) -> Vec<Complex<f64>> {
+    let mut buffer = data.to_vec();
+    FftPlanner::new()
+        .plan_fft_inverse(buffer.len())
+        .process(&mut buffer);
+    buffer
+}
@@ -0,0 +1,200 @@
+use autodiff::FT;
+use num_complex::Complex;
+use num_traits::Float;
+
+use crate::{
+    conversion::{mass_ratio_from_symmetric_mass_ratio, total_mass_from_chirp_mass},
+    inner_product::trapezoid_weights,
+    numeric::solve,
+    psd::PowerSpectralDensity,
+    waveform::Waveform,
+};
+
+/// Order of the parameters in the Fisher matrix, `t_c` in seconds and `phi_c` in radians.
+pub const FISHER_PARAMETERS: [&str; 7] = [
+    "ln_chirp_mass",
+    "eta",
+    "chi_1",
+    "chi_2",
+    "t_c",
+    "phi_c",
+    "ln_luminosity_distance",
+];
+
+/// Derivative of the strain with respect to one of the `FISHER_PARAMETERS`.
+///
+/// `model` builds the waveform from the total mass, mass ratio, spins and luminosity
+/// distance in Mpc, as the `TaylorF2` and `IMRPhenomD` constructors take them. The mass
+/// ratio is not differentiable at eta = 1/4 so eta must be strictly below that.
+pub fn waveform_derivative<W, F>(
+    model: &F,
+    parameters: &[f64; 7],
+    index: usize,
+    frequencies: &[f64],
+) -> Vec<Complex<f64>>
+where
+    W: Waveform<FT<f64>>,
+    F: Fn(FT<f64>, FT<f64>, FT<f64>, FT<f64>, FT<f64>) -> W,
+{
+    let theta: Vec<FT<f64>> = parameters
+        .iter()
+        .enumerate()
+        .map(|(ii, &value)| {
+            if ii == index {
+                FT::var(value)
+            } else {
+                FT::cst(value)
+            }
+        })
+        .collect();
+    let mass_ratio = mass_ratio_from_symmetric_mass_ratio(theta[1]);
+    let total_mass = total_mass_from_chirp_mass(theta[0].exp(), mass_ratio);
+    let waveform = model(total_mass, mass_ratio, theta[2], theta[3], theta[6].exp());
+    frequencies
+        .iter()
+        .map(|&frequency| {
+            let strain = waveform.waveform_single_frequency(FT::cst(frequency), theta[5], theta[4]);
+            Complex::new(strain.re.deriv(), strain.im.deriv())
+        })
+        .collect()
+}
+
+/// Fisher information of the face-on signal overhead of a single detector, in the order
+/// of `FISHER_PARAMETERS`.
+pub fn fisher_matrix<W, F>(
+    model: F,
+    parameters: &[f64; 7],
+    frequencies: &[f64],
+    psd: &PowerSpectralDensity,
+) -> [[f64; 7]; 7]
+where
+    W: Waveform<FT<f64>>,
+    F: Fn(FT<f64>, FT<f64>, FT<f64>, FT<f64>, FT<f64>) -> W,
+{
+    // drop frequencies outside the sensitive band, the model may not be finite there
+    let (frequencies, weights): (Vec<f64>, Vec<f64>) = frequencies
+        .iter()
+        .zip(trapezoid_weights(frequencies))
+        .zip(psd.evaluate(frequencies))
+        .map(|((&frequency, weight), power)| (frequency, 4.0 * weight / power))
+        .filter(|&(_, weight)| weight > 0.0)
+        .unzip();
+    let derivatives: Vec<Vec<Complex<f64>>> = (0..7)
+        .map(|index| waveform_derivative(&model, parameters, index, &frequencies))
+        .collect();
+
+    let mut fisher = [[0.0; 7]; 7];
+    for ii in 0..7 {
+        for jj in 0..=ii {
+            let value = derivatives[ii]
+                .iter()
+                .zip(derivatives[jj].iter())
+                .zip(weights.iter())
+                .map(|((a, b), weight)| (a * b.conj()).re * weight)
+                .sum();
+            fisher[ii][jj] = value;
+            fisher[jj][ii] = value;
+        }
+    }
+    fisher
+}
+
+/// Inverse of the Fisher matrix, the Cramér-Rao bound on the parameter covariance.
+pub fn covariance(fisher: &[[f64; 7]; 7]) -> [[f64; 7]; 7] {
+    // normalise by the diagonal first, the entries span many orders of magnitude
+    let scale: [f64; 7] = std::array::from_fn(|ii| fisher[ii][ii].sqrt());
+    let normalised: [[f64; 7]; 7] = std::array::from_fn(|ii| {
+        std::array::from_fn(|jj| fisher[ii][jj] / (scale[ii] * scale[jj]))
+    });
+    let columns: [[f64; 7]; 7] = std::array::from_fn(|jj| {
+        let mut unit = [0.0; 7];
+        unit[jj] = 1.0;
+        solve(normalised, unit, f64::abs)
+    });
+    std::array::from_fn(|ii| std::array::from_fn(|jj| columns[jj][ii] / (scale[ii] * scale[jj])))
+}
+
+/// One-sigma uncertainties from the diagonal of the covariance.
+pub fn parameter_errors(fisher: &[[f64; 7]; 7]) -> [f64; 7] {
+    let covariance = covariance(fisher);
+    std::array::from_fn(|ii| covariance[ii][ii].sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taylor::TaylorF2;
+
+    const PARAMETERS: [f64; 7] = [2.5, 0.24, 0.3, -0.2, 0.01, 0.4, 6.0];
+
+    fn frequencies() -> Vec<f64> {
+        (0..400).map(|ii| 20.0 + 0.5 * ii as f64).collect()
+    }
+
+    fn strain(parameters: &[f64; 7], frequencies: &[f64]) -> Vec<Complex<f64>> {
+        let mass_ratio = mass_ratio_from_symmetric_mass_ratio(parameters[1]);
+        let total_mass = total_mass_from_chirp_mass(parameters[0].exp(), mass_ratio);
+        let model = TaylorF2::new(
+            total_mass,
+            mass_ratio,
+            parameters[2],
+            parameters[3],
+            parameters[6].exp(),
+            20.0,
+        );
+        model
+            .waveform(frequencies, parameters[5], parameters[4])
+            .data
+    }
+
+    #[test]
+    fn derivatives_match_central_differences() {
+        let frequencies = frequencies();
+        let step = 1e-6;
+        for index in 0..7 {
+            let derivative = waveform_derivative(
+                &|m, q, c1, c2, d| TaylorF2::<FT<f64>>::new(m, q, c1, c2, d, 20.0),
+                &PARAMETERS,
+                index,
+                &frequencies,
+            );
+            let shifted = |offset: f64| {
+                let mut parameters = PARAMETERS;
+                parameters[index] += offset;
+                strain(&parameters, &frequencies)
+            };
+            let (upper, lower) = (shifted(step), shifted(-step));
+            let scale = derivative.iter().map(|d| d.norm()).fold(0.0, f64::max);
+            for (d, (u, l)) in derivative.iter().zip(upper.iter().zip(lower.iter())) {
+                let numerical = (u - l) / (2.0 * step);
+                assert!(
+                    (d - numerical).norm() < 1e-5 * scale,
+                    "{}: {d} != {numerical}",
+                    FISHER_PARAMETERS[index]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn covariance_inverts_the_fisher_matrix() {
+        let fisher = fisher_matrix(
+            |m, q, c1, c2, d| TaylorF2::<FT<f64>>::new(m, q, c1, c2, d, 20.0),
+            &PARAMETERS,
+            &frequencies(),
+            &PowerSpectralDensity::AdvancedLIGO,
+        );
+        let covariance = covariance(&fisher);
+        for (ii, jj) in (0..7).flat_map(|ii| (0..7).map(move |jj| (ii, jj))) {
+            // the entries span many orders of magnitude, so compare the normalised product
+            let product: f64 = (0..7).map(|kk| fisher[ii][kk] * covariance[kk][jj]).sum();
+            let scale = (fisher[ii][ii] * covariance[jj][jj]).sqrt();
+            let expected = if ii == jj { 1.0 } else { 0.0 };
+            assert!((product - expected).abs() < 1e-6 * scale.max(1.0));
+        }
+        // dh / d ln D = -h so that entry is the squared SNR, and marginalising over the
+        // other parameters only widens the error
+        let snr = fisher[6][6].sqrt();
+        assert!(parameter_errors(&fisher)[6] >= 1.0 / snr);
+    }
+}
@@ -0,0 +1,314 @@
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+
+use num_complex::Complex;
+
+use crate::{
+    constants::SOLAR_RADIUS_IN_S,
+    fft::rfft,
+    pn::Params,
+    series::{FrequencySeries, TimeSeries, Unit},
+    time_domain::TimeDomainWaveform,
+    waveform::Waveform,
+};
+
+const TAPER_CYCLES: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrequencyDomainError {
+    /// `delta_t`, `delta_f` or `f_lower` is not positive and finite.
+    InvalidSpacing,
+    /// The generated time series does not fit in 1 / delta_f.
+    TooLong { duration: f64, max_duration: f64 },
+    /// Fewer than two frequencies between `f_lower` and Nyquist.
+    EmptyBand { f_lower: f64, nyquist: f64 },
+    /// The reference frequency is not in the generated band.
+    ReferenceFrequencyOutsideBand {
+        reference_frequency: f64,
+        f_lower: f64,
+        f_upper: f64,
+    },
+}
+
+impl fmt::Display for FrequencyDomainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidSpacing => write!(f, "delta_t, delta_f and f_lower must be positive"),
+            Self::TooLong {
+                duration,
+                max_duration,
+            } => write!(
+                f,
+                "time series of duration {duration} s is longer than 1 / delta_f = {max_duration} s"
+            ),
+            Self::EmptyBand { f_lower, nyquist } => write!(
+                f,
+                "no frequencies between f_lower = {f_lower} Hz and Nyquist = {nyquist} Hz"
+            ),
+            Self::ReferenceFrequencyOutsideBand {
+                reference_frequency,
+                f_lower,
+                f_upper,
+            } => write!(
+                f,
+                "reference frequency {reference_frequency} Hz is outside the generated band \
+                 {f_lower} Hz to {f_upper} Hz"
+            ),
+        }
+    }
+}
+
+impl Error for FrequencyDomainError {}
+
+fn valid_spacing(value: f64) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+pub fn frequency_domain_strain(
+    series: &TimeSeries,
+    f_lower: f64,
+    delta_f: f64,
+) -> Result<FrequencySeries, FrequencyDomainError> {
+    let delta_t = series.delta_t;
+    if !(valid_spacing(delta_t) && valid_spacing(delta_f) && valid_spacing(f_lower)) {
+        return Err(FrequencyDomainError::InvalidSpacing);
+    }
+    let length = (1.0 / (delta_f * delta_t)).round() as usize;
+    if series.len() > length {
+        return Err(FrequencyDomainError::TooLong {
+            duration: series.duration(),
+            max_duration: 1.0 / delta_f,
+        });
+    }
+
+    // taper the first few cycles at f_lower then zero-pad to the requested resolution
+    let n_taper = ((TAPER_CYCLES / f_lower / delta_t).round() as usize).min(series.len());
+    let mut data = vec![0.0; length];
+    for (ii, (value, &h)) in data.iter_mut().zip(series.data.iter()).enumerate() {
+        let taper = if ii < n_taper {
+            0.5 - 0.5 * (PI * ii as f64 / n_taper as f64).cos()
+        } else {
+            1.0
+        };
+        *value = h * taper;
+    }
+
+    // the transform places the first sample at t = 0, shift it back to the epoch
+    let delta_f = 1.0 / (length as f64 * delta_t);
+    let data = rfft(&data)
+        .iter()
+        .enumerate()
+        .map(|(k, &h)| {
+            h * delta_t * Complex::new(0.0, -2.0 * PI * k as f64 * delta_f * series.epoch).exp()
+        })
+        .collect();
+    Ok(FrequencySeries::uniform(
+        data,
+        0.0,
+        delta_f,
+        0.0,
+        Unit::StrainPerHertz,
+    ))
+}
+
+fn unwrap(phase: &mut [f64]) {
+    let mut offset = 0.0;
+    for ii in 1..phase.len() {
+        let previous = phase[ii - 1];
+        let mut current = phase[ii] + offset;
+        while current - previous > PI {
+            current -= 2.0 * PI;
+            offset -= 2.0 * PI;
+        }
+        while current - previous < -PI {
+            current += 2.0 * PI;
+            offset += 2.0 * PI;
+        }
+        phase[ii] = current;
+    }
+}
+
+// amplitude and unwrapped phase, with the sign convention of `Waveform::phase`, from
+// `k_lower` up
+fn tabulate(strain: &FrequencySeries, k_lower: usize) -> (Vec<f64>, Vec<f64>) {
+    let amplitude = strain.data[k_lower..].iter().map(|h| h.norm()).collect();
+    let mut phase: Vec<f64> = strain.data[k_lower..].iter().map(|h| -h.arg()).collect();
+    unwrap(&mut phase);
+    (amplitude, phase)
+}
+
+/// Frequency-domain view of a time-domain generator.
+///
+/// t = 0 is the origin of the generator's time series. Both polarisations are tabulated
+/// face-on, other inclinations scale them by (1 + cos^2 theta_jn) / 2 and cos theta_jn
+/// as for the dominant mode. `phi_c` rotates them as the dominant mode so `amplitude`
+/// and `phase` describe the face-on plus polarisation.
+#[derive(Clone, Debug)]
+pub struct FrequencyDomain<G> {
+    pub generator: G,
+    pub delta_f: f64,
+    pub f_lower: f64,
+    reference_frequency: f64,
+    reference_phase: f64,
+    k_lower: usize,
+    amplitude: Vec<f64>,
+    phase: Vec<f64>,
+    cross_amplitude: Vec<f64>,
+    cross_phase: Vec<f64>,
+}
+
+impl<G: TimeDomainWaveform> FrequencyDomain<G> {
+    pub fn new(
+        generator: G,
+        delta_t: f64,
+        f_lower: f64,
+        delta_f: f64,
+        reference_frequency: f64,
+    ) -> Result<Self, FrequencyDomainError> {
+        let (plus, cross) = generator.waveform_modes(delta_t, f_lower, 0.0, 0.0, 0.0);
+        let plus = frequency_domain_strain(&plus, f_lower, delta_f)?;
+        let cross = frequency_domain_strain(&cross, f_lower, delta_f)?;
+        let k_lower = (f_lower / delta_f).ceil() as usize;
+        if k_lower + 2 > plus.len() {
+            return Err(FrequencyDomainError::EmptyBand {
+                f_lower,
+                nyquist: 0.5 / delta_t,
+            });
+        }
+        let (amplitude, phase) = tabulate(&plus, k_lower);
+        let (cross_amplitude, cross_phase) = tabulate(&cross, k_lower);
+        let mut new = Self {
+            generator,
+            delta_f,
+            f_lower,
+            reference_frequency,
+            reference_phase: 0.0,
+            k_lower,
+            amplitude,
+            phase,
+            cross_amplitude,
+            cross_phase,
+        };
+        new.reference_phase = new.interpolate(&new.phase, reference_frequency).ok_or(
+            FrequencyDomainError::ReferenceFrequencyOutsideBand {
+                reference_frequency,
+                f_lower: k_lower as f64 * delta_f,
+                f_upper: new.end_frequency(),
+            },
+        )?;
+        Ok(new)
+    }
+
+    // tables hold at least two samples
+    fn interpolate(&self, values: &[f64], frequency: f64) -> Option<f64> {
+        let position = frequency / self.delta_f - self.k_lower as f64;
+        if position < 0.0 || position > (values.len() - 1) as f64 {
+            return None;
+        }
+        let index = (position.floor() as usize).min(values.len() - 2);
+        let fraction = position - index as f64;
+        Some(values[index] * (1.0 - fraction) + values[index + 1] * fraction)
+    }
+
+    fn polarisation(
+        &self,
+        amplitude: &[f64],
+        phase: &[f64],
+        frequency: f64,
+        phi_c: f64,
+        t_c: f64,
+    ) -> Complex<f64> {
+        match (
+            self.interpolate(amplitude, frequency),
+            self.interpolate(phase, frequency),
+        ) {
+            (Some(amplitude), Some(phase)) if frequency <= self.end_frequency() => {
+                Complex::from_polar(
+                    amplitude,
+                    -(phase - self.reference_phase - 2.0 * phi_c) - 2.0 * PI * frequency * t_c,
+                )
+            }
+            _ => Complex::new(0.0, 0.0),
+        }
+    }
+
+    // inverse of `orbital_speed`
+    fn frequency(&self, v: f64) -> f64 {
+        v.powi(3) / (PI * self.total_mass() * SOLAR_RADIUS_IN_S)
+    }
+}
+
+impl<G: TimeDomainWaveform> Waveform for FrequencyDomain<G> {
+    fn total_mass(&self) -> f64 {
+        self.generator.total_mass()
+    }
+
+    fn params(&self) -> &Params {
+        self.generator.params()
+    }
+
+    fn luminosity_distance(&self) -> f64 {
+        self.generator.luminosity_distance()
+    }
+
+    fn reference_frequency(&self) -> f64 {
+        self.reference_frequency
+    }
+
+    fn orbital_speed(&self, frequency: f64) -> f64 {
+        (PI * self.total_mass() * SOLAR_RADIUS_IN_S * frequency).cbrt()
+    }
+
+    fn phase(&self, v: f64, phi_c: f64) -> f64 {
+        let frequency = self.frequency(v);
+        self.interpolate(&self.phase, frequency).unwrap_or(0.0) - self.reference_phase - 2.0 * phi_c
+    }
+
+    fn amplitude(&self, v: f64) -> f64 {
+        self.interpolate(&self.amplitude, self.frequency(v))
+            .unwrap_or(0.0)
+    }
+
+    // the last generated frequency
+    fn end_frequency(&self) -> f64 {
+        (self.k_lower + self.phase.len() - 1) as f64 * self.delta_f
+    }
+
+    // slope of the interpolated segment
+    fn phase_derivative(&self, frequency: f64) -> f64 {
+        let position = frequency / self.delta_f - self.k_lower as f64;
+        if position < 0.0 || position > (self.phase.len() - 1) as f64 {
+            return 0.0;
+        }
+        let index = (position.floor() as usize).min(self.phase.len() - 2);
+        (self.phase[index + 1] - self.phase[index]) / self.delta_f
+    }
+
+    // the tables are indexed by frequency, skip the orbital speed
+    fn waveform_single_frequency(&self, frequency: f64, phi_c: f64, t_c: f64) -> Complex<f64> {
+        self.polarisation(&self.amplitude, &self.phase, frequency, phi_c, t_c)
+    }
+
+    fn modes_single_frequency(
+        &self,
+        frequency: f64,
+        phi_c: f64,
+        t_c: f64,
+        theta_jn: f64,
+    ) -> (Complex<f64>, Complex<f64>) {
+        let cos_iota = theta_jn.cos();
+        (
+            (1.0 + cos_iota.powi(2)) / 2.0
+                * self.polarisation(&self.amplitude, &self.phase, frequency, phi_c, t_c),
+            cos_iota
+                * self.polarisation(
+                    &self.cross_amplitude,
+                    &self.cross_phase,
+                    frequency,
+                    phi_c,
+                    t_c,
+                ),
+        )
+    }
+}
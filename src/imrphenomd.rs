@@ -1,9 +1,10 @@
-use std::f64::consts::PI;
-
 use crate::{
     constants::{MPC_SI, SOLAR_RADIUS_IN_M, SOLAR_RADIUS_IN_S},
+    cosmology::{detector_frame_mass, Cosmology},
+    numeric::{cast, Real},
+    parameters::BinaryParameters,
     pn::{PNPhasing, Params},
-    waveform::Waveform,
+    waveform::{Waveform, WaveformGenerator},
 };
 
 mod amplitude;
@@ -12,62 +13,181 @@ mod constants;
 mod phase;
 mod utils;
 
+// the calibration region ends at Mf = 0.2
+const MF_END: f64 = 0.2;
+
 use amplitude::{imrphenomd_amplitude, AmplitudePrefactors};
-use phase::{imrphenomd_phase, mrd_phase_derivative, PhaseCoefficients, inspiral_prefactors};
+use phase::{
+    imrphenomd_phase, imrphenomd_phase_derivative, inspiral_prefactors, mrd_phase_derivative,
+    PhaseCoefficients,
+};
 
+/// Phenomenological inspiral-merger-ringdown model for aligned spins.
+///
+/// t = 0 is the peak of the amplitude, `t0` removes the slope of the phase at `f_peak`.
 #[derive(Clone, Copy, Debug)]
-pub struct IMRPhenomD {
-    pub total_mass: f64,
-    pub luminosity_distance: f64,
-    coeffs: PhaseCoefficients,
-    phase_prefactors: PNPhasing,
-    amplitude_prefactors: AmplitudePrefactors,
-    t0: f64,
+pub struct IMRPhenomD<T = f64> {
+    pub total_mass: T,
+    pub luminosity_distance: T,
+    params: Params<T>,
+    coeffs: PhaseCoefficients<T>,
+    phase_prefactors: PNPhasing<T>,
+    amplitude_prefactors: AmplitudePrefactors<T>,
+    t0: T,
+    reference_frequency: f64,
+    reference_phase: T,
 }
 
-impl IMRPhenomD {
+impl<T: Real> IMRPhenomD<T> {
+    /// The phase is referenced at `reference_frequency` in Hz, which must be positive and
+    /// finite.
     pub fn new(
-        total_mass: f64,
-        mass_ratio: f64,
-        chi_1: f64,
-        chi_2: f64,
-        luminosity_distance: f64,
+        total_mass: T,
+        mass_ratio: T,
+        chi_1: T,
+        chi_2: T,
+        luminosity_distance: T,
+        reference_frequency: f64,
     ) -> Self {
+        assert!(
+            reference_frequency.is_finite() && reference_frequency > 0.0,
+            "reference frequency {reference_frequency} Hz is not positive"
+        );
         let params = Params::new(mass_ratio, chi_1, chi_2);
         let coeffs = PhaseCoefficients::new(&params);
         let phase_prefactors = inspiral_prefactors(&params, &coeffs);
         let amplitude_prefactors = AmplitudePrefactors::new(&params);
 
-        Self {
+        let mut new = Self {
             total_mass,
-            luminosity_distance: luminosity_distance * MPC_SI,
+            luminosity_distance: luminosity_distance * cast(MPC_SI),
+            params,
             coeffs,
             phase_prefactors,
             amplitude_prefactors,
             t0: mrd_phase_derivative(amplitude_prefactors.f_peak, &coeffs),
-        }
+            reference_frequency,
+            reference_phase: T::zero(),
+        };
+        new.reference_phase = new.aligned_phase(new.orbital_speed(cast(reference_frequency)));
+        new
+    }
+
+    /// Build from the source-frame total mass at `redshift`, the luminosity distance
+    /// follows from `cosmology`.
+    pub fn from_source_frame(
+        source_frame_total_mass: T,
+        mass_ratio: T,
+        chi_1: T,
+        chi_2: T,
+        redshift: T,
+        cosmology: &Cosmology,
+        reference_frequency: f64,
+    ) -> Self {
+        Self::new(
+            detector_frame_mass(source_frame_total_mass, redshift),
+            mass_ratio,
+            chi_1,
+            chi_2,
+            cosmology.luminosity_distance(redshift),
+            reference_frequency,
+        )
+    }
+
+    /// Ringdown frequency of the final black hole in Hz.
+    pub fn ringdown_frequency(&self) -> T {
+        self.coeffs.f_ringdown / (self.total_mass * cast(SOLAR_RADIUS_IN_S))
+    }
+
+    /// Frequency of the peak of the amplitude in Hz, the model's t = 0.
+    pub fn peak_frequency(&self) -> T {
+        self.amplitude_prefactors.f_peak / (self.total_mass * cast(SOLAR_RADIUS_IN_S))
     }
-}
 
-impl Waveform for IMRPhenomD {
-    fn phase(&self, v: f64, phi_c: f64) -> f64 {
+    fn aligned_phase(&self, v: T) -> T {
         imrphenomd_phase(v, &self.coeffs, &self.phase_prefactors)
-            - phi_c
             - self.t0 * (v - self.amplitude_prefactors.f_peak)
     }
+}
+
+impl<T: Real> Waveform<T> for IMRPhenomD<T> {
+    fn total_mass(&self) -> T {
+        self.total_mass
+    }
+
+    fn params(&self) -> &Params<T> {
+        &self.params
+    }
+
+    fn luminosity_distance(&self) -> T {
+        self.luminosity_distance
+    }
 
-    fn amplitude(&self, v: f64) -> f64 {
-        let amp0 = 2.
-            * (5.0 / (64.0 * PI)).sqrt()
+    fn reference_frequency(&self) -> f64 {
+        self.reference_frequency
+    }
+
+    fn phase(&self, v: T, phi_c: T) -> T {
+        self.aligned_phase(v) - self.reference_phase - cast::<T>(2.0) * phi_c
+    }
+
+    fn end_frequency(&self) -> f64 {
+        MF_END / (self.total_mass.to_f64().unwrap() * SOLAR_RADIUS_IN_S)
+    }
+
+    fn phase_derivative(&self, frequency: T) -> T {
+        // the phase is a function of Mf
+        let mass_in_seconds = self.total_mass * cast(SOLAR_RADIUS_IN_S);
+        (imrphenomd_phase_derivative(
+            self.orbital_speed(frequency),
+            &self.coeffs,
+            &self.phase_prefactors,
+        ) - self.t0)
+            * mass_in_seconds
+    }
+
+    fn amplitude(&self, v: T) -> T {
+        let c = cast::<T>;
+        let amp0 = c(2.0)
+            * ((c(5.0) / c(64.0)) / T::PI()).sqrt()
             * self.total_mass
-            * SOLAR_RADIUS_IN_M
+            * c(SOLAR_RADIUS_IN_M)
             * self.total_mass
-            * SOLAR_RADIUS_IN_S
+            * c(SOLAR_RADIUS_IN_S)
             / self.luminosity_distance;
         amp0 * imrphenomd_amplitude(v, &self.amplitude_prefactors)
     }
 
-    fn orbital_speed(&self, frequency: f64) -> f64 {
-        frequency * self.total_mass * SOLAR_RADIUS_IN_S
+    fn orbital_speed(&self, frequency: T) -> T {
+        frequency * self.total_mass * cast(SOLAR_RADIUS_IN_S)
+    }
+}
+
+/// `IMRPhenomD` from `BinaryParameters` for any scalar type.
+#[derive(Clone, Copy, Debug)]
+pub struct IMRPhenomDGenerator {
+    pub reference_frequency: f64,
+}
+
+impl IMRPhenomDGenerator {
+    pub fn new(reference_frequency: f64) -> Self {
+        Self {
+            reference_frequency,
+        }
+    }
+}
+
+impl<T: Real> WaveformGenerator<T> for IMRPhenomDGenerator {
+    type Output = IMRPhenomD<T>;
+
+    fn generate(&self, parameters: &BinaryParameters<T>) -> IMRPhenomD<T> {
+        IMRPhenomD::new(
+            parameters.total_mass,
+            parameters.mass_ratio,
+            parameters.chi_1,
+            parameters.chi_2,
+            parameters.luminosity_distance,
+            self.reference_frequency,
+        )
     }
 }
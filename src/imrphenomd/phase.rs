@@ -1,31 +1,29 @@
-use std::f64::consts::PI;
-
 use crate::imrphenomd::coefficients::{ALPHA_COEFFS, BETA_COEFFS, SIGMA_COEFFS};
-use crate::imrphenomd::constants::POWERS_OF_PI;
 use crate::imrphenomd::constants::PHI_FJOIN_INS;
-use crate::imrphenomd::utils::{fdamp, final_spin_0815, fring, phenomenological_function};
+use crate::imrphenomd::utils::{fdamp, final_spin_0815, fring, phenomenological_function, Powers};
+use crate::numeric::{cast, Real};
 use crate::pn::{chi_pn, taylor_3pn_ss, PNPhasing, Params};
 
 #[derive(Clone, Copy, Debug)]
-pub(crate) struct PhaseCoefficients {
-    eta_inv: f64,
+pub(crate) struct PhaseCoefficients<T = f64> {
+    eta_inv: T,
 
-    pub(crate) sigma: [f64; 4],
-    beta: [f64; 3],
-    alpha: [f64; 5],
-    f_ringdown: f64,
-    f_damping: f64,
+    pub(crate) sigma: [T; 4],
+    beta: [T; 3],
+    alpha: [T; 5],
+    pub(crate) f_ringdown: T,
+    f_damping: T,
 
-    intermediate_connection: [f64; 2],
-    mrd_connection: [f64; 2],
+    intermediate_connection: [T; 2],
+    mrd_connection: [T; 2],
 }
 
-impl PhaseCoefficients {
-    pub fn new(params: &Params) -> Self {
+impl<T: Real> PhaseCoefficients<T> {
+    pub fn new(params: &Params<T>) -> Self {
         let eta = params.eta;
-        let eta_inv = 1.0 / eta;
+        let eta_inv = T::one() / eta;
         let chi = chi_pn(params);
-        let xi = chi - 1.0;
+        let xi = chi - T::one();
 
         let sigma = [
             phenomenological_function(eta, xi, &SIGMA_COEFFS[0]),
@@ -56,11 +54,11 @@ impl PhaseCoefficients {
             alpha,
             f_ringdown,
             f_damping,
-            intermediate_connection: [0.0, 0.0],
-            mrd_connection: [0.0, 0.0],
+            intermediate_connection: [T::zero(); 2],
+            mrd_connection: [T::zero(); 2],
         };
 
-        let prefactors = inspiral_prefactors(&params, &new);
+        let prefactors = inspiral_prefactors(params, &new);
         let (intermediate_connection, mrd_connection) =
             phase_connection_coefficients(&new, &prefactors);
         new.intermediate_connection = intermediate_connection;
@@ -69,30 +67,34 @@ impl PhaseCoefficients {
     }
 }
 
-pub(crate) fn inspiral_prefactors(params: &Params, coeffs: &PhaseCoefficients) -> PNPhasing {
-    let mut pn = PNPhasing::new(&params);
-    let _3pnss = taylor_3pn_ss(&params) * pn.v[0];
-    pn.v[5] -= PI / 4.0;
+pub(crate) fn inspiral_prefactors<T: Real>(
+    params: &Params<T>,
+    coeffs: &PhaseCoefficients<T>,
+) -> PNPhasing<T> {
+    let c = cast::<T>;
+    let powers_of_pi = Powers::new(T::PI());
+    let mut pn = PNPhasing::new(params);
+    let _3pnss = taylor_3pn_ss(params) * pn.v[0];
+    pn.v[5] -= T::PI() / c(4.0);
     pn.v[6] -= _3pnss;
-    pn.v[8] = coeffs.sigma[0] / params.eta / POWERS_OF_PI.one;
-    pn.v[9] = coeffs.sigma[1] * 3.0 / 4.0 / params.eta / POWERS_OF_PI.four_thirds;
-    pn.v[10] = coeffs.sigma[2] * 3.0 / 5.0 / params.eta / POWERS_OF_PI.five_thirds;
-    pn.v[11] = coeffs.sigma[3] / 2.0 / params.eta / POWERS_OF_PI.two;
+    pn.v[8] = coeffs.sigma[0] / params.eta / powers_of_pi.one;
+    pn.v[9] = coeffs.sigma[1] * c(3.0) / c(4.0) / params.eta / powers_of_pi.four_thirds;
+    pn.v[10] = coeffs.sigma[2] * c(3.0) / c(5.0) / params.eta / powers_of_pi.five_thirds;
+    pn.v[11] = coeffs.sigma[3] / c(2.0) / params.eta / powers_of_pi.two;
     pn
 }
 
-fn phase_connection_coefficients(
-    coeffs: &PhaseCoefficients,
-    prefactors: &PNPhasing,
-) -> ([f64; 2], [f64; 2]) {
-    let f2: f64 = coeffs.f_ringdown / 2.0;
+fn phase_connection_coefficients<T: Real>(
+    coeffs: &PhaseCoefficients<T>,
+    prefactors: &PNPhasing<T>,
+) -> ([T; 2], [T; 2]) {
+    let f1 = cast::<T>(PHI_FJOIN_INS);
+    let f2 = coeffs.f_ringdown / cast(2.0);
 
-    let d_phi_ins = inspiral_phase_derivative(PHI_FJOIN_INS, prefactors);
-    let d_phi_int = intermediate_phase_derivative(PHI_FJOIN_INS, coeffs);
+    let d_phi_ins = inspiral_phase_derivative(f1, prefactors);
+    let d_phi_int = intermediate_phase_derivative(f1, coeffs);
     let c2_int = d_phi_ins - d_phi_int;
-    let c1_int = inspiral_phase(PHI_FJOIN_INS, prefactors)
-        - intermediate_phase(PHI_FJOIN_INS, coeffs)
-        - c2_int * PHI_FJOIN_INS;
+    let c1_int = inspiral_phase(f1, prefactors) - intermediate_phase(f1, coeffs) - c2_int * f1;
 
     let intermediate_phase_connection = intermediate_phase(f2, coeffs) + c1_int + c2_int * f2;
     let d_phi_int = c2_int + intermediate_phase_derivative(f2, coeffs);
@@ -103,11 +105,11 @@ fn phase_connection_coefficients(
     ([c1_int, c2_int], [c1_mrd, c2_mrd])
 }
 
-fn inspiral_phase(frequency: f64, prefactors: &PNPhasing) -> f64 {
-    let orbital_speed = frequency.cbrt() * POWERS_OF_PI.third;
+fn inspiral_phase<T: Real>(frequency: T, prefactors: &PNPhasing<T>) -> T {
+    let orbital_speed = (T::PI() * frequency).cbrt();
     let logv = orbital_speed.ln();
 
-    let mut phasing = 0.0;
+    let mut phasing = T::zero();
     let mut cumulative_orbital_speed = orbital_speed.powi(-5);
 
     for ii in 0..13 {
@@ -119,40 +121,43 @@ fn inspiral_phase(frequency: f64, prefactors: &PNPhasing) -> f64 {
     phasing
 }
 
-fn inspiral_phase_derivative(frequency: f64, prefactors: &PNPhasing) -> f64 {
-    let orbital_speed = frequency.cbrt() * POWERS_OF_PI.third;
+fn inspiral_phase_derivative<T: Real>(frequency: T, prefactors: &PNPhasing<T>) -> T {
+    let c = cast::<T>;
+    let orbital_speed = (T::PI() * frequency).cbrt();
     let logv = orbital_speed.ln();
 
-    let mut phasing = 0.0;
+    let mut phasing = T::zero();
     let mut cumulative_orbital_speed = orbital_speed.powi(-5);
-    let mut power = -5.0 / 3.0;
+    let mut power = c(-5.0) / c(3.0);
 
     for ii in 0..13 {
         phasing += power * prefactors.v[ii] * cumulative_orbital_speed;
-        phasing += prefactors.vlogv[ii] * cumulative_orbital_speed * (power * logv + 1.0 / 3.0);
+        phasing +=
+            prefactors.vlogv[ii] * cumulative_orbital_speed * (power * logv + c(1.0) / c(3.0));
         cumulative_orbital_speed *= orbital_speed;
-        power += 1.0 / 3.0;
+        power += c(1.0) / c(3.0);
     }
 
     phasing / frequency
 }
 
-fn intermediate_phase(frequency: f64, prefactors: &PhaseCoefficients) -> f64 {
-    (prefactors.beta[0] * frequency - prefactors.beta[2] / (3.0 * frequency.powi(3))
+fn intermediate_phase<T: Real>(frequency: T, prefactors: &PhaseCoefficients<T>) -> T {
+    (prefactors.beta[0] * frequency - prefactors.beta[2] / (cast::<T>(3.0) * frequency.powi(3))
         + prefactors.beta[1] * frequency.ln())
         * prefactors.eta_inv
 }
 
-fn intermediate_phase_derivative(frequency: f64, prefactors: &PhaseCoefficients) -> f64 {
+fn intermediate_phase_derivative<T: Real>(frequency: T, prefactors: &PhaseCoefficients<T>) -> T {
     (prefactors.beta[0] + prefactors.beta[2] / frequency.powi(4) + prefactors.beta[1] / frequency)
         * prefactors.eta_inv
 }
 
-fn mrd_phase(frequency: f64, prefactors: &PhaseCoefficients) -> f64 {
-    let rho_lm = 1.0;
-    let tau_lm = 1.0;
+fn mrd_phase<T: Real>(frequency: T, prefactors: &PhaseCoefficients<T>) -> T {
+    let c = cast::<T>;
+    let rho_lm = T::one();
+    let tau_lm = T::one();
     (prefactors.alpha[0] * frequency - prefactors.alpha[1] / frequency
-        + 4.0 / 3.0 * prefactors.alpha[2] * frequency.powf(0.75)
+        + (c(4.0) / c(3.0)) * prefactors.alpha[2] * frequency.powf(c(0.75))
         + prefactors.alpha[3]
             * rho_lm
             * ((frequency - prefactors.alpha[4] * prefactors.f_ringdown)
@@ -161,28 +166,28 @@ fn mrd_phase(frequency: f64, prefactors: &PhaseCoefficients) -> f64 {
         * prefactors.eta_inv
 }
 
-pub(crate) fn mrd_phase_derivative(frequency: f64, prefactors: &PhaseCoefficients) -> f64 {
-    let rho_lm = 1.0;
-    let tau_lm = 1.0;
+pub(crate) fn mrd_phase_derivative<T: Real>(frequency: T, prefactors: &PhaseCoefficients<T>) -> T {
+    let rho_lm = T::one();
+    let tau_lm = T::one();
     (prefactors.alpha[0]
         + prefactors.alpha[1] / frequency.powi(2)
-        + prefactors.alpha[2] / frequency.powf(0.25)
+        + prefactors.alpha[2] / frequency.powf(cast(0.25))
         + prefactors.alpha[3]
             / (prefactors.f_damping
                 * tau_lm
-                * (1.0
+                * (T::one()
                     + (frequency - prefactors.alpha[4] * prefactors.f_ringdown).powi(2)
                         / (prefactors.f_damping * tau_lm * rho_lm).powi(2))))
         * prefactors.eta_inv
 }
 
-pub fn imrphenomd_phase(
-    frequency: f64,
-    coeffs: &PhaseCoefficients,
-    prefactors: &PNPhasing,
-) -> f64 {
-    let f1 = PHI_FJOIN_INS;
-    let f2 = coeffs.f_ringdown / 2.0;
+pub fn imrphenomd_phase<T: Real>(
+    frequency: T,
+    coeffs: &PhaseCoefficients<T>,
+    prefactors: &PNPhasing<T>,
+) -> T {
+    let f1 = cast::<T>(PHI_FJOIN_INS);
+    let f2 = coeffs.f_ringdown / cast(2.0);
     match frequency {
         f if f < f1 => inspiral_phase(f, prefactors),
         f if f1 <= f && f < f2 => {
@@ -193,6 +198,23 @@ pub fn imrphenomd_phase(
         f if f2 <= f => {
             mrd_phase(f, coeffs) + coeffs.mrd_connection[0] + coeffs.mrd_connection[1] * f
         }
-        _ => 0.0,
+        _ => T::zero(),
+    }
+}
+
+pub fn imrphenomd_phase_derivative<T: Real>(
+    frequency: T,
+    coeffs: &PhaseCoefficients<T>,
+    prefactors: &PNPhasing<T>,
+) -> T {
+    let f1 = cast::<T>(PHI_FJOIN_INS);
+    let f2 = coeffs.f_ringdown / cast(2.0);
+    match frequency {
+        f if f < f1 => inspiral_phase_derivative(f, prefactors),
+        f if f1 <= f && f < f2 => {
+            intermediate_phase_derivative(f, coeffs) + coeffs.intermediate_connection[1]
+        }
+        f if f2 <= f => mrd_phase_derivative(f, coeffs) + coeffs.mrd_connection[1],
+        _ => T::zero(),
     }
 }
@@ -0,0 +1,123 @@
+use num_complex::Complex;
+
+use crate::{
+    network::Network, parameters::ExtrinsicParameters, psd::PowerSpectralDensity,
+    series::FrequencySeries, waveform::Waveform,
+};
+
+/// Trapezoid integration weights, these reduce to delta_f away from the edges of a
+/// uniform grid.
+pub fn trapezoid_weights(frequencies: &[f64]) -> Vec<f64> {
+    let n = frequencies.len();
+    let mut weights = vec![0.0; n];
+    for ii in 1..n {
+        let width = (frequencies[ii] - frequencies[ii - 1]) / 2.0;
+        weights[ii - 1] += width;
+        weights[ii] += width;
+    }
+    weights
+}
+
+// 4 sum(w a b* / S), the noise weighted inner product before taking the real part.
+pub fn complex_inner_product(
+    a: &FrequencySeries,
+    b: &FrequencySeries,
+    psd: &[f64],
+) -> Complex<f64> {
+    assert_eq!(a.len(), b.len(), "frequency series have different lengths");
+    assert_eq!(
+        a.len(),
+        psd.len(),
+        "psd and frequency series have different lengths"
+    );
+    let weights = trapezoid_weights(a.frequencies());
+    4.0 * a
+        .data
+        .iter()
+        .zip(b.data.iter())
+        .zip(weights.iter().zip(psd.iter()))
+        .map(|((&x, &y), (&weight, &power))| x * y.conj() * (weight / power))
+        .sum::<Complex<f64>>()
+}
+
+pub fn inner_product(a: &FrequencySeries, b: &FrequencySeries, psd: &[f64]) -> f64 {
+    complex_inner_product(a, b, psd).re
+}
+
+pub fn snr_squared(signal: &FrequencySeries, psd: &[f64]) -> f64 {
+    inner_product(signal, signal, psd)
+}
+
+/// Optimal SNR of the face-on signal overhead of a single detector.
+pub fn optimal_snr<W: Waveform>(model: &W, frequencies: &[f64], psd: &PowerSpectralDensity) -> f64 {
+    snr_squared(
+        &model.waveform(frequencies, 0.0, 0.0),
+        &psd.evaluate(frequencies),
+    )
+    .sqrt()
+}
+
+pub fn network_snr<W: Waveform>(
+    model: &W,
+    network: &Network,
+    psds: &[PowerSpectralDensity],
+    frequencies: &[f64],
+    parameters: &ExtrinsicParameters,
+) -> f64 {
+    assert_eq!(network.len(), psds.len(), "need one psd per detector");
+    network
+        .strain(model, frequencies, parameters, parameters.geocent_time)
+        .iter()
+        .zip(psds.iter())
+        .map(|(strain, psd)| snr_squared(strain, &psd.evaluate(frequencies)))
+        .sum::<f64>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{series::Unit, taylor::TaylorF2};
+
+    fn series(values: impl Fn(f64) -> Complex<f64>) -> FrequencySeries {
+        let frequencies: Vec<f64> = (0..101).map(|ii| 20.0 + 0.5 * ii as f64).collect();
+        let data = frequencies.iter().map(|&f| values(f)).collect();
+        FrequencySeries::new(data, frequencies, 0.0, Unit::StrainPerHertz)
+    }
+
+    #[test]
+    fn trapezoid_weights_integrate_the_band() {
+        let weights = trapezoid_weights(&[1.0, 2.0, 3.0, 5.0]);
+        assert_eq!(weights, vec![0.5, 1.0, 1.5, 1.0]);
+        assert_eq!(weights.iter().sum::<f64>(), 4.0);
+    }
+
+    #[test]
+    fn inner_product_of_a_constant_is_four_times_the_bandwidth() {
+        let a = series(|_| Complex::new(3.0, 4.0));
+        let psd = vec![2.0; a.len()];
+        // 4 |a|^2 (f_upper - f_lower) / S
+        assert!((inner_product(&a, &a, &psd) - 4.0 * 25.0 * 50.0 / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn complex_inner_product_is_hermitian() {
+        let a = series(|f| Complex::from_polar(1.0 / f, 0.1 * f));
+        let b = series(|f| Complex::from_polar(f.sqrt(), -0.03 * f * f));
+        let psd: Vec<f64> = a.frequencies().iter().map(|f| 1.0 + f).collect();
+        let ab = complex_inner_product(&a, &b, &psd);
+        let ba = complex_inner_product(&b, &a, &psd);
+        assert!((ab - ba.conj()).norm() < 1e-12 * ab.norm());
+        assert!(complex_inner_product(&a, &a, &psd).im.abs() < 1e-12);
+    }
+
+    #[test]
+    fn optimal_snr_is_inversely_proportional_to_distance() {
+        let frequencies: Vec<f64> = (0..2000).map(|ii| 20.0 + 0.25 * ii as f64).collect();
+        let psd = PowerSpectralDensity::AdvancedLIGO;
+        let near = TaylorF2::new(30.0, 0.8, 0.1, -0.2, 400.0, 20.0);
+        let far = TaylorF2::new(30.0, 0.8, 0.1, -0.2, 1600.0, 20.0);
+        let ratio = optimal_snr(&near, &frequencies, &psd) / optimal_snr(&far, &frequencies, &psd);
+        assert!((ratio - 4.0).abs() < 1e-10);
+    }
+}
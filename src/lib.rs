@@ -1,5 +1,27 @@
 pub(crate) mod constants;
+pub mod conversion;
+pub mod cosmology;
+pub mod detector;
+pub mod fft;
+pub mod fisher;
+pub mod frequency_domain;
 pub mod imrphenomd;
+pub mod inner_product;
+pub mod likelihood;
+pub mod marginalisation;
+pub mod multibanding;
+pub mod nested_sampling;
+pub mod network;
+pub mod numeric;
+pub mod overlap;
+pub mod parameters;
 pub mod pn;
+pub mod prior;
+pub mod psd;
+pub mod relative_binning;
+pub mod roq;
+pub mod series;
 pub mod taylor;
+pub mod time;
+pub mod time_domain;
 pub mod waveform;
@@ -0,0 +1,317 @@
+use std::sync::Mutex;
+
+use autodiff::FT;
+use num_complex::Complex;
+
+use crate::{
+    constants::MPC_SI,
+    inner_product::trapezoid_weights,
+    network::Network,
+    parameters::BinaryParameters,
+    psd::PowerSpectralDensity,
+    series::{FrequencySeries, Unit},
+    waveform::{Waveform, WaveformGenerator},
+};
+
+// Polarisations and projected strain on the data frequencies, reused between calls.
+struct Scratch {
+    plus: FrequencySeries,
+    cross: FrequencySeries,
+    strain: FrequencySeries,
+}
+
+impl Scratch {
+    fn new(frequencies: &[f64]) -> Self {
+        let series = FrequencySeries::zeros(frequencies.to_vec(), 0.0, Unit::StrainPerHertz);
+        Self {
+            plus: series.clone(),
+            cross: series.clone(),
+            strain: series,
+        }
+    }
+}
+
+/// Stationary Gaussian noise likelihood for frequency-domain data in a detector network.
+///
+/// The data of every detector must share the frequencies and epoch of the first.
+/// `model` builds the waveform for each parameter set, its luminosity distance is
+/// rescaled to the requested one so the model may be built at any distance. The
+/// polarisations are written into buffers kept between calls, one set per concurrent
+/// caller.
+pub struct GaussianLikelihood<F> {
+    pub network: Network,
+    pub data: Vec<FrequencySeries>,
+    pub model: F,
+    frequencies: Vec<f64>,
+    weights: Vec<Vec<f64>>,
+    d_inner_d: f64,
+    scratch: Mutex<Vec<Scratch>>,
+}
+
+impl<F: WaveformGenerator> GaussianLikelihood<F> {
+    pub fn new(
+        network: Network,
+        data: Vec<FrequencySeries>,
+        psds: &[PowerSpectralDensity],
+        model: F,
+    ) -> Self {
+        assert_eq!(network.len(), data.len(), "need data for each detector");
+        assert_eq!(network.len(), psds.len(), "need one psd per detector");
+        assert!(!data.is_empty(), "need at least one detector");
+        let frequencies = data[0].frequencies().to_vec();
+        let integration = trapezoid_weights(&frequencies);
+        let weights: Vec<Vec<f64>> = psds
+            .iter()
+            .map(|psd| {
+                integration
+                    .iter()
+                    .zip(psd.evaluate(&frequencies))
+                    .map(|(weight, power)| 4.0 * weight / power)
+                    .collect()
+            })
+            .collect();
+        let mut new = Self {
+            network,
+            data,
+            model,
+            frequencies,
+            weights,
+            d_inner_d: 0.0,
+            scratch: Mutex::new(Vec::new()),
+        };
+        for (strain, weights) in new.data.iter().zip(new.weights.iter()) {
+            // all detectors share the frequencies and epoch of the first
+            new.data[0].check_compatible(strain);
+            new.d_inner_d += strain
+                .data
+                .iter()
+                .zip(weights.iter())
+                .map(|(value, weight)| value.norm_sqr() * weight)
+                .sum::<f64>();
+        }
+        new
+    }
+
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    pub fn d_inner_d(&self) -> f64 {
+        self.d_inner_d
+    }
+
+    /// <d|h> and <h|h> summed over the network.
+    pub fn inner_products(&self, parameters: &BinaryParameters) -> (f64, f64) {
+        let (d_inner_h, h_inner_h) = self.complex_inner_products(parameters);
+        (d_inner_h.re, h_inner_h)
+    }
+
+    // <d|h> before taking the real part, with the signal at the requested phase and time.
+    pub(crate) fn complex_inner_products(
+        &self,
+        parameters: &BinaryParameters,
+    ) -> (Complex<f64>, f64) {
+        let mut d_inner_h = Complex::new(0.0, 0.0);
+        let h_inner_h = self.accumulate(parameters, |_, value| d_inner_h += value);
+        (d_inner_h, h_inner_h)
+    }
+
+    // The network summed integrand of <d|h> at each frequency and <h|h>.
+    pub(crate) fn integrand(&self, parameters: &BinaryParameters) -> (Vec<Complex<f64>>, f64) {
+        let mut integrand = vec![Complex::new(0.0, 0.0); self.frequencies.len()];
+        let h_inner_h = self.accumulate(parameters, |ii, value| integrand[ii] += value);
+        (integrand, h_inner_h)
+    }
+
+    fn accumulate<G: FnMut(usize, Complex<f64>)>(
+        &self,
+        parameters: &BinaryParameters,
+        mut sink: G,
+    ) -> f64 {
+        let mut scratch = self
+            .scratch
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(|| Scratch::new(&self.frequencies));
+        let model = self.model.generate(parameters);
+        let scale = model.luminosity_distance() / (parameters.luminosity_distance * MPC_SI);
+        model.fill_waveform_modes(
+            parameters.phi_c,
+            0.0,
+            parameters.theta_jn,
+            &mut scratch.plus,
+            &mut scratch.cross,
+        );
+        for value in scratch
+            .plus
+            .data
+            .iter_mut()
+            .chain(scratch.cross.data.iter_mut())
+        {
+            *value *= scale;
+        }
+        let extrinsic = parameters.extrinsic();
+        let mut h_inner_h = 0.0;
+        for ((detector, strain), weights) in self
+            .network
+            .detectors
+            .iter()
+            .zip(self.data.iter())
+            .zip(self.weights.iter())
+        {
+            detector.project_into(
+                &scratch.plus,
+                &scratch.cross,
+                &extrinsic,
+                strain.epoch,
+                &mut scratch.strain,
+            );
+            for (ii, (&signal, &weight)) in
+                scratch.strain.data.iter().zip(weights.iter()).enumerate()
+            {
+                sink(ii, strain.data[ii] * signal.conj() * weight);
+                h_inner_h += signal.norm_sqr() * weight;
+            }
+        }
+        self.scratch.lock().unwrap().push(scratch);
+        h_inner_h
+    }
+
+    pub fn noise_log_likelihood(&self) -> f64 {
+        -self.d_inner_d / 2.0
+    }
+
+    pub fn log_likelihood_ratio(&self, parameters: &BinaryParameters) -> f64 {
+        let (d_inner_h, h_inner_h) = self.inner_products(parameters);
+        d_inner_h - h_inner_h / 2.0
+    }
+
+    // -<d - h|d - h> / 2, dropping the normalisation of the noise distribution.
+    pub fn log_likelihood(&self, parameters: &BinaryParameters) -> f64 {
+        self.log_likelihood_ratio(parameters) + self.noise_log_likelihood()
+    }
+}
+
+// The gradients rebuild the model on dual numbers, which needs a generator for every scalar
+// type rather than an f64 closure.
+impl<F: WaveformGenerator + WaveformGenerator<FT<f64>>> GaussianLikelihood<F> {
+    /// Log likelihood ratio and its gradient with respect to the `BINARY_PARAMETERS`, see
+    /// `Network::strain_gradient`.
+    pub fn log_likelihood_ratio_gradient(&self, parameters: &BinaryParameters) -> (f64, [f64; 11]) {
+        // the phase at GPS times is only accurate relative to a nearby epoch
+        let (strains, derivatives) = self.network.strain_gradient(
+            &self.model,
+            &self.frequencies,
+            parameters,
+            self.data[0].epoch,
+        );
+        let mut log_likelihood_ratio = 0.0;
+        let mut gradient = [0.0; 11];
+        for (((data, weights), strain), derivatives) in self
+            .data
+            .iter()
+            .zip(self.weights.iter())
+            .zip(strains.iter())
+            .zip(derivatives.iter())
+        {
+            // d lnL / d theta = <d - h|dh / d theta>
+            let strain = strain.with_epoch(data.epoch);
+            let residual: Vec<Complex<f64>> = data
+                .data
+                .iter()
+                .zip(strain.data.iter())
+                .zip(weights.iter())
+                .map(|((&d, &h), &weight)| {
+                    log_likelihood_ratio += weight * ((d * h.conj()).re - h.norm_sqr() / 2.0);
+                    (d - h) * weight
+                })
+                .collect();
+            for (value, derivative) in gradient.iter_mut().zip(derivatives.iter()) {
+                let derivative = derivative.with_epoch(data.epoch);
+                *value += residual
+                    .iter()
+                    .zip(derivative.data.iter())
+                    .map(|(r, h)| (r * h.conj()).re)
+                    .sum::<f64>();
+            }
+        }
+        (log_likelihood_ratio, gradient)
+    }
+
+    pub fn log_likelihood_gradient(&self, parameters: &BinaryParameters) -> (f64, [f64; 11]) {
+        let (log_likelihood_ratio, gradient) = self.log_likelihood_ratio_gradient(parameters);
+        (log_likelihood_ratio + self.noise_log_likelihood(), gradient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::taylor::TaylorF2Generator;
+
+    fn injection() -> (Network, Vec<FrequencySeries>, BinaryParameters) {
+        let truth = BinaryParameters {
+            total_mass: 30.0,
+            mass_ratio: 0.7,
+            chi_1: 0.2,
+            chi_2: 0.0,
+            ra: 1.0,
+            dec: -0.5,
+            psi: 0.3,
+            theta_jn: 0.4,
+            phi_c: 1.1,
+            geocent_time: 1126259462.4,
+            luminosity_distance: 800.0,
+        };
+        let frequencies: Vec<f64> = (0..721).map(|ii| 20.0 + 0.25 * ii as f64).collect();
+        let network = Network::from_names(&["H1", "L1"]).unwrap();
+        let model = TaylorF2Generator::new(20.0).generate(&truth);
+        let data = network.strain(
+            &model,
+            &frequencies,
+            &truth.extrinsic(),
+            truth.geocent_time - 2.0,
+        );
+        (network, data, truth)
+    }
+
+    fn likelihood(
+        network: Network,
+        data: Vec<FrequencySeries>,
+    ) -> GaussianLikelihood<TaylorF2Generator> {
+        let psds = [
+            PowerSpectralDensity::AdvancedLIGO,
+            PowerSpectralDensity::AdvancedLIGO,
+        ];
+        GaussianLikelihood::new(network, data, &psds, TaylorF2Generator::new(20.0))
+    }
+
+    #[test]
+    fn noise_free_injection_fits_the_data_exactly() {
+        let (network, data, truth) = injection();
+        let likelihood = likelihood(network, data);
+        let (d_inner_h, h_inner_h) = likelihood.inner_products(&truth);
+        let d_inner_d = likelihood.d_inner_d();
+        assert!((d_inner_h - d_inner_d).abs() < 1e-10 * d_inner_d);
+        assert!((h_inner_h - d_inner_d).abs() < 1e-10 * d_inner_d);
+        assert!(likelihood.log_likelihood(&truth).abs() < 1e-10 * d_inner_d);
+    }
+
+    #[test]
+    #[should_panic(expected = "different epochs")]
+    fn detectors_must_share_an_epoch() {
+        let (network, mut data, _) = injection();
+        data[1] = data[1].with_epoch(data[1].epoch + 1.0);
+        likelihood(network, data);
+    }
+
+    #[test]
+    #[should_panic(expected = "different frequencies")]
+    fn detectors_must_share_frequencies() {
+        let (network, mut data, _) = injection();
+        let shifted = data[1].frequencies().iter().map(|f| f + 0.1).collect();
+        data[1] = FrequencySeries::new(data[1].data.clone(), shifted, data[1].epoch, data[1].units);
+        likelihood(network, data);
+    }
+}
@@ -0,0 +1,459 @@
+use std::f64::consts::PI;
+
+use num_complex::Complex;
+
+use crate::{
+    fft::ifft, likelihood::GaussianLikelihood, parameters::BinaryParameters,
+    waveform::WaveformGenerator,
+};
+
+const DISTANCE_SAMPLES: usize = 500;
+const PRIOR_SAMPLES: usize = 2048;
+const WINDOW_WIDTHS: f64 = 10.0;
+const TABLE_SIZE: usize = 400;
+const TABLE_EXTENT: f64 = 1e6;
+const TIME_OVERSAMPLING: f64 = 16.0;
+
+/// Natural log of the modified Bessel function I0 (Abramowitz & Stegun 9.8.1, 9.8.2).
+pub fn log_i0(x: f64) -> f64 {
+    let x = x.abs();
+    if x <= 3.75 {
+        let t = (x / 3.75).powi(2);
+        (1.0 + t
+            * (3.5156229
+                + t * (3.0899424
+                    + t * (1.2067492 + t * (0.2659732 + t * (0.0360768 + t * 0.0045813))))))
+            .ln()
+    } else {
+        let t = 3.75 / x;
+        let series = 0.39894228
+            + t * (0.01328592
+                + t * (0.00225319
+                    + t * (-0.00157565
+                        + t * (0.00916281
+                            + t * (-0.02057706
+                                + t * (0.02635537 + t * (-0.01647633 + t * 0.00392377)))))));
+        x - 0.5 * x.ln() + series.ln()
+    }
+}
+
+fn log_sum_exp(values: &[f64]) -> f64 {
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values
+        .iter()
+        .map(|value| (value - max).exp())
+        .sum::<f64>()
+        .ln()
+}
+
+/// Lookup table of the likelihood ratio marginalised over luminosity distance.
+///
+/// The table is indexed by <d|h> (|<d|h>| when also marginalising over phase) and <h|h>
+/// evaluated at the largest distance, the integral over the prior is stored relative to
+/// the maximum of the integrand over the prior range and interpolated bilinearly.
+///
+/// Each integral is taken in log distance over the prior range, narrowed to ten widths of
+/// the likelihood around its peak so the samples resolve the peak at any SNR. The table is
+/// only smooth enough to interpolate when that window lies inside the prior range, other
+/// likelihoods, including those beyond the extent of the table, are integrated directly.
+#[derive(Clone, Debug)]
+pub struct DistanceMarginalisation {
+    pub phase_marginalised: bool,
+    reference_distance: f64,
+    rho_min: f64,
+    rho_max: f64,
+    // normalised prior density per unit log distance, uniform in log distance
+    log_prior: Vec<f64>,
+    table: Vec<f64>,
+}
+
+impl DistanceMarginalisation {
+    /// `prior` is the (unnormalised) density in Mpc over [d_min, d_max].
+    pub fn new<P: Fn(f64) -> f64>(
+        prior: P,
+        d_min: f64,
+        d_max: f64,
+        phase_marginalised: bool,
+    ) -> Self {
+        assert!(0.0 < d_min && d_min < d_max, "need 0 < d_min < d_max");
+        let step = (d_max / d_min).ln() / (PRIOR_SAMPLES - 1) as f64;
+        let mut log_prior: Vec<f64> = (0..PRIOR_SAMPLES)
+            .map(|ii| {
+                let distance = d_min * (ii as f64 * step).exp();
+                (prior(distance) * distance).ln()
+            })
+            .collect();
+        let log_weights: Vec<f64> = log_prior
+            .iter()
+            .enumerate()
+            .map(|(ii, value)| {
+                let edge = if ii == 0 || ii == PRIOR_SAMPLES - 1 {
+                    0.5
+                } else {
+                    1.0
+                };
+                value + (edge * step).ln()
+            })
+            .collect();
+        let normalisation = log_sum_exp(&log_weights);
+        log_prior
+            .iter_mut()
+            .for_each(|value| *value -= normalisation);
+
+        let mut new = Self {
+            phase_marginalised,
+            reference_distance: d_max,
+            rho_min: 1.0,
+            rho_max: d_max / d_min,
+            log_prior,
+            table: vec![0.0; TABLE_SIZE * TABLE_SIZE],
+        };
+        for ii in 0..TABLE_SIZE {
+            let x = new.x_axis(ii);
+            for jj in 0..TABLE_SIZE {
+                let y = new.y_axis(jj);
+                new.table[ii * TABLE_SIZE + jj] = new.log_integral(x, y) - new.peak(x, y);
+            }
+        }
+        new
+    }
+
+    // Linear interpolation of the log prior in log(d_max / d) = log(rho).
+    fn log_prior_at(&self, log_rho: f64) -> f64 {
+        let scaled = (1.0 - log_rho / self.rho_max.ln()) * (PRIOR_SAMPLES - 1) as f64;
+        let scaled = scaled.clamp(0.0, (PRIOR_SAMPLES - 1) as f64);
+        let ii = (scaled.floor() as usize).min(PRIOR_SAMPLES - 2);
+        let fraction = scaled - ii as f64;
+        let (lower, upper) = (self.log_prior[ii], self.log_prior[ii + 1]);
+        // avoid 0 * -inf at the nodes where the prior vanishes
+        if fraction == 0.0 {
+            lower
+        } else if fraction == 1.0 {
+            upper
+        } else {
+            (1.0 - fraction) * lower + fraction * upper
+        }
+    }
+
+    // ln of the integral over the prior of exp(exponent) by the trapezoid rule in log
+    // distance, the likelihood has width 1 / sqrt(y) in rho about its peak.
+    fn log_integral(&self, x: f64, y: f64) -> f64 {
+        let (lower, upper) = self.window(x, y);
+        let (start, end) = (lower.max(self.rho_min).ln(), upper.min(self.rho_max).ln());
+        let step = (end - start) / (DISTANCE_SAMPLES - 1) as f64;
+        let terms: Vec<f64> = (0..DISTANCE_SAMPLES)
+            .map(|ii| {
+                let log_rho = start + ii as f64 * step;
+                let edge = if ii == 0 || ii == DISTANCE_SAMPLES - 1 {
+                    0.5
+                } else {
+                    1.0
+                };
+                self.log_prior_at(log_rho) + (edge * step).ln() + self.exponent(x, y, log_rho.exp())
+            })
+            .collect();
+        log_sum_exp(&terms)
+    }
+
+    pub fn reference_distance(&self) -> f64 {
+        self.reference_distance
+    }
+
+    fn x_extent(&self) -> (f64, f64) {
+        let upper = TABLE_EXTENT.asinh();
+        if self.phase_marginalised {
+            (0.0, upper)
+        } else {
+            (-upper, upper)
+        }
+    }
+
+    fn x_axis(&self, ii: usize) -> f64 {
+        let (lower, upper) = self.x_extent();
+        (lower + (upper - lower) * ii as f64 / (TABLE_SIZE - 1) as f64).sinh()
+    }
+
+    fn y_axis(&self, jj: usize) -> f64 {
+        (TABLE_EXTENT.asinh() * jj as f64 / (TABLE_SIZE - 1) as f64).sinh()
+    }
+
+    fn exponent(&self, x: f64, y: f64, rho: f64) -> f64 {
+        let overlap = x * rho;
+        let overlap = if self.phase_marginalised {
+            log_i0(overlap)
+        } else {
+            overlap
+        };
+        overlap - y * rho * rho / 2.0
+    }
+
+    // The integrand at the best distance in the prior range, removing the rapid variation
+    // of the table.
+    fn peak(&self, x: f64, y: f64) -> f64 {
+        self.exponent(x, y, self.peak_rho(x, y))
+    }
+
+    // Ten widths of the likelihood either side of its peak in the prior range.
+    fn window(&self, x: f64, y: f64) -> (f64, f64) {
+        if y > 0.0 {
+            let centre = self.peak_rho(x, y);
+            let width = WINDOW_WIDTHS / y.sqrt();
+            (centre - width, centre + width)
+        } else {
+            (f64::NEG_INFINITY, f64::INFINITY)
+        }
+    }
+
+    fn peak_rho(&self, x: f64, y: f64) -> f64 {
+        let rho = if y > 0.0 { x / y } else { self.rho_max };
+        rho.clamp(self.rho_min, self.rho_max)
+    }
+
+    /// Marginalised ln L ratio from <d|h> and <h|h> computed at `distance`.
+    pub fn log_likelihood_ratio(&self, d_inner_h: f64, h_inner_h: f64, distance: f64) -> f64 {
+        let ratio = distance / self.reference_distance;
+        let x = d_inner_h * ratio;
+        let y = h_inner_h * ratio * ratio;
+        let (lower, upper) = self.x_extent();
+        let scaled = |value: f64, lower: f64, upper: f64| {
+            (value.asinh() - lower) / (upper - lower) * (TABLE_SIZE - 1) as f64
+        };
+        let u = scaled(x, lower, upper);
+        let w = scaled(y, 0.0, TABLE_EXTENT.asinh());
+        let last = (TABLE_SIZE - 1) as f64;
+        let (window_lower, window_upper) = self.window(x, y);
+        let interior = window_lower > self.rho_min && window_upper < self.rho_max;
+        if !(interior && (0.0..=last).contains(&u) && (0.0..=last).contains(&w)) {
+            return self.log_integral(x, y);
+        }
+        let ii = (u.floor() as usize).min(TABLE_SIZE - 2);
+        let jj = (w.floor() as usize).min(TABLE_SIZE - 2);
+        let (du, dw) = (u - ii as f64, w - jj as f64);
+        let value = |a: usize, b: usize| self.table[a * TABLE_SIZE + b];
+        let residual = (1.0 - du) * ((1.0 - dw) * value(ii, jj) + dw * value(ii, jj + 1))
+            + du * ((1.0 - dw) * value(ii + 1, jj) + dw * value(ii + 1, jj + 1));
+        residual + self.peak(x, y)
+    }
+}
+
+/// Gaussian likelihood marginalised over any of phase, distance and time of coalescence.
+///
+/// The marginalised parameters in the parameter set are ignored, except `geocent_time`
+/// which is the centre of the uniform time prior of width `time_window` seconds. Time
+/// marginalisation shifts all detectors together and needs a uniform frequency grid.
+pub struct MarginalisedLikelihood<F> {
+    pub likelihood: GaussianLikelihood<F>,
+    pub phase: bool,
+    pub distance: Option<DistanceMarginalisation>,
+    pub time_window: Option<f64>,
+}
+
+impl<F: WaveformGenerator> MarginalisedLikelihood<F> {
+    pub fn new(
+        likelihood: GaussianLikelihood<F>,
+        phase: bool,
+        distance: Option<DistanceMarginalisation>,
+        time_window: Option<f64>,
+    ) -> Self {
+        if let Some(distance) = &distance {
+            assert_eq!(
+                distance.phase_marginalised, phase,
+                "distance table must be built with the same phase marginalisation"
+            );
+        }
+        if let Some(window) = time_window {
+            let delta_f = likelihood.data[0]
+                .delta_f()
+                .expect("time marginalisation requires uniformly spaced frequencies");
+            assert!(
+                window > 0.0 && window < 1.0 / delta_f,
+                "time window must be shorter than the data"
+            );
+        }
+        Self {
+            likelihood,
+            phase,
+            distance,
+            time_window,
+        }
+    }
+
+    fn marginalised(&self, d_inner_h: Complex<f64>, h_inner_h: f64, distance: f64) -> f64 {
+        let overlap = if self.phase {
+            d_inner_h.norm()
+        } else {
+            d_inner_h.re
+        };
+        match &self.distance {
+            Some(table) => table.log_likelihood_ratio(overlap, h_inner_h, distance),
+            None if self.phase => log_i0(overlap) - h_inner_h / 2.0,
+            None => overlap - h_inner_h / 2.0,
+        }
+    }
+
+    pub fn log_likelihood_ratio(&self, parameters: &BinaryParameters) -> f64 {
+        let mut parameters = *parameters;
+        if let Some(table) = &self.distance {
+            parameters.luminosity_distance = table.reference_distance();
+        }
+        let distance = parameters.luminosity_distance;
+        let window = match self.time_window {
+            Some(window) => window,
+            None => {
+                let (d_inner_h, h_inner_h) = self.likelihood.complex_inner_products(&parameters);
+                return self.marginalised(d_inner_h, h_inner_h, distance);
+            }
+        };
+
+        let frequencies = self.likelihood.frequencies();
+        let delta_f = self.likelihood.data[0].delta_f().unwrap();
+        let (integrand, h_inner_h) = self.likelihood.integrand(&parameters);
+        let length = (frequencies.len() as f64)
+            .max(TIME_OVERSAMPLING * frequencies[frequencies.len() - 1] / delta_f)
+            .ceil() as usize;
+        let mut buffer = vec![Complex::new(0.0, 0.0); length.next_power_of_two()];
+        buffer[..integrand.len()].copy_from_slice(&integrand);
+        let overlaps = ifft(&buffer);
+        let n = overlaps.len();
+        let delta_t = 1.0 / (n as f64 * delta_f);
+        let values: Vec<f64> = overlaps
+            .iter()
+            .enumerate()
+            .filter_map(|(ii, &overlap)| {
+                let shift = if ii < n / 2 {
+                    ii as f64
+                } else {
+                    ii as f64 - n as f64
+                } * delta_t;
+                (shift.abs() <= window / 2.0).then(|| {
+                    let offset = Complex::new(0.0, 2.0 * PI * frequencies[0] * shift).exp();
+                    self.marginalised(overlap * offset, h_inner_h, distance)
+                })
+            })
+            .collect();
+        log_sum_exp(&values) + (delta_t / window).ln()
+    }
+
+    pub fn log_likelihood(&self, parameters: &BinaryParameters) -> f64 {
+        self.log_likelihood_ratio(parameters) + self.likelihood.noise_log_likelihood()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        network::Network, psd::PowerSpectralDensity, series::FrequencySeries,
+        taylor::TaylorF2Generator,
+    };
+
+    // ln of the prior average of the likelihood ratio over a fine uniform distance grid
+    fn brute_force_distance(d_inner_h: f64, h_inner_h: f64, distance: f64, phase: bool) -> f64 {
+        let (d_min, d_max, samples) = (50.0, 5000.0, 1_000_000);
+        let step = (d_max - d_min) / samples as f64;
+        let (mut terms, mut priors) = (Vec::new(), Vec::new());
+        for ii in 0..samples {
+            let trial = d_min + (ii as f64 + 0.5) * step;
+            let ratio = distance / trial;
+            let overlap = d_inner_h * ratio;
+            let overlap = if phase { log_i0(overlap) } else { overlap };
+            let log_prior = 2.0 * trial.ln();
+            terms.push(log_prior + overlap - h_inner_h * ratio * ratio / 2.0);
+            priors.push(log_prior);
+        }
+        log_sum_exp(&terms) - log_sum_exp(&priors)
+    }
+
+    #[test]
+    fn distance_marginalisation_matches_direct_integration() {
+        for phase in [false, true] {
+            let table = DistanceMarginalisation::new(|d| d * d, 50.0, 5000.0, phase);
+            // low SNR, peaks inside the prior at SNR 40 and 630 read from the table, one at
+            // the furthest distance and one beyond the extent of the table
+            for (d_inner_h, h_inner_h, distance) in [
+                (3.0, 10.0, 1000.0),
+                (80.0, 4.0, 5000.0),
+                (2e4, 1e3, 2000.0),
+                (1e4, 1e4, 5000.0),
+                (1.6e7, 4e6, 500.0),
+            ] {
+                let value = table.log_likelihood_ratio(d_inner_h, h_inner_h, distance);
+                let expected = brute_force_distance(d_inner_h, h_inner_h, distance, phase);
+                assert!(
+                    (value - expected).abs() < 1e-4,
+                    "phase {phase}, <d|h> = {d_inner_h}, <h|h> = {h_inner_h}: \
+                     {value} != {expected}"
+                );
+            }
+        }
+    }
+
+    fn injection() -> (GaussianLikelihood<TaylorF2Generator>, BinaryParameters) {
+        let truth = BinaryParameters {
+            total_mass: 30.0,
+            mass_ratio: 0.7,
+            chi_1: 0.2,
+            chi_2: 0.0,
+            ra: 1.0,
+            dec: -0.5,
+            psi: 0.3,
+            theta_jn: 0.4,
+            phi_c: 1.1,
+            geocent_time: 1126259462.4,
+            luminosity_distance: 2000.0,
+        };
+        let frequencies: Vec<f64> = (0..721).map(|ii| 20.0 + 0.25 * ii as f64).collect();
+        let network = Network::from_names(&["H1", "L1"]).unwrap();
+        let model = TaylorF2Generator::new(20.0);
+        let data: Vec<FrequencySeries> = network.strain(
+            &model.generate(&truth),
+            &frequencies,
+            &truth.extrinsic(),
+            truth.geocent_time - 2.0,
+        );
+        let psds = [
+            PowerSpectralDensity::AdvancedLIGO,
+            PowerSpectralDensity::AdvancedLIGO,
+        ];
+        (GaussianLikelihood::new(network, data, &psds, model), truth)
+    }
+
+    #[test]
+    fn phase_marginalisation_matches_direct_integration() {
+        let (likelihood, truth) = injection();
+        let mut parameters = truth;
+        parameters.total_mass = 30.01;
+        let samples = 256;
+        let terms: Vec<f64> = (0..samples)
+            .map(|ii| {
+                parameters.phi_c = 2.0 * PI * ii as f64 / samples as f64;
+                likelihood.log_likelihood_ratio(&parameters)
+            })
+            .collect();
+        let expected = log_sum_exp(&terms) - (samples as f64).ln();
+        let marginalised = MarginalisedLikelihood::new(likelihood, true, None, None);
+        let value = marginalised.log_likelihood_ratio(&parameters);
+        // log_i0 is accurate to a few parts in 1e7
+        assert!((value - expected).abs() < 1e-5, "{value} != {expected}");
+    }
+
+    #[test]
+    fn time_marginalisation_matches_direct_integration() {
+        let (likelihood, truth) = injection();
+        let (window, samples) = (0.02, 2000);
+        let terms: Vec<f64> = (0..samples)
+            .map(|ii| {
+                let mut parameters = truth;
+                parameters.geocent_time += window * ((ii as f64 + 0.5) / samples as f64 - 0.5);
+                likelihood.log_likelihood_ratio(&parameters)
+            })
+            .collect();
+        let expected = log_sum_exp(&terms) - (samples as f64).ln();
+        let marginalised = MarginalisedLikelihood::new(likelihood, false, None, Some(window));
+        let value = marginalised.log_likelihood_ratio(&truth);
+        assert!((value - expected).abs() < 1e-3, "{value} != {expected}");
+    }
+}
@@ -0,0 +1,205 @@
+use std::f64::consts::PI;
+
+use num_complex::Complex;
+
+use crate::{
+    series::{FrequencySeries, Unit},
+    waveform::Waveform,
+};
+
+const MINIMUM_WIDTH: usize = 4;
+
+/// Evaluate a model on the uniform grid f_lower + k delta_f <= f_upper by multibanding.
+///
+/// The phase and log amplitude are computed on a coarse grid and interpolated
+/// quadratically onto the fine grid. Each interval is as wide as the interpolation error
+/// bound allows for an error of `threshold` (radians in phase, relative in amplitude),
+/// given the time to merger at its start frequency. The error is checked at the quarter
+/// points and the interval halved if the estimate fails, which happens through merger
+/// where the inspiral scaling does not hold. Within an interval the strain is filled in by
+/// recurrence without further calls to the model, where even four samples are too wide the
+/// model is evaluated directly.
+pub fn multibanded_waveform<W: Waveform>(
+    model: &W,
+    f_lower: f64,
+    f_upper: f64,
+    delta_f: f64,
+    phi_c: f64,
+    t_c: f64,
+    threshold: f64,
+) -> FrequencySeries {
+    let n_total = ((f_upper - f_lower) / delta_f).floor() as usize + 1;
+    // the strain is zero beyond the end of the model
+    let f_end = f_upper.min(model.end_frequency());
+    if f_end < f_lower {
+        let data = vec![Complex::new(0.0, 0.0); n_total];
+        return FrequencySeries::uniform(data, f_lower, delta_f, 0.0, Unit::StrainPerHertz);
+    }
+    let n = ((f_end - f_lower) / delta_f).floor() as usize + 1;
+    let evaluate = |ii: usize| {
+        let frequency = f_lower + ii as f64 * delta_f;
+        let orbital_speed = model.orbital_speed(frequency);
+        (
+            model.amplitude(orbital_speed).ln(),
+            model.phase(orbital_speed, phi_c) + 2.0 * PI * frequency * t_c,
+        )
+    };
+    let strain = |(log_amplitude, phase): (f64, f64)| Complex::new(log_amplitude, -phase).exp();
+
+    let mut data = Vec::with_capacity(n);
+    let mut start = 0;
+    let mut first = evaluate(0);
+    while n - 1 - start >= MINIMUM_WIDTH {
+        let remaining = (n - 1 - start) / MINIMUM_WIDTH * MINIMUM_WIDTH;
+        let estimate = band_width(model, f_lower + start as f64 * delta_f, threshold) / delta_f;
+        let mut width = estimate.min(remaining as f64) as usize / MINIMUM_WIDTH * MINIMUM_WIDTH;
+        let band = (width > 0).then(|| loop {
+            let middle = evaluate(start + width / 2);
+            let last = evaluate(start + width);
+            let quarters = [
+                (0.25, evaluate(start + width / 4)),
+                (0.75, evaluate(start + 3 * width / 4)),
+            ];
+            let mut error: f64 = 0.0;
+            for (x, (log_amplitude, phase)) in quarters {
+                let fit = interpolate(first, middle, last, x);
+                error = error
+                    .max((fit.0 - log_amplitude).abs())
+                    .max((fit.1 - phase).abs());
+            }
+            if error <= threshold {
+                break Some((middle, last));
+            }
+            if width == MINIMUM_WIDTH {
+                break None;
+            }
+            width = (width / 2 / MINIMUM_WIDTH).max(1) * MINIMUM_WIDTH;
+        });
+        // even the narrowest interval is too wide, so this sample is evaluated directly
+        let Some(Some((middle, last))) = band else {
+            data.push(strain(first));
+            start += 1;
+            first = evaluate(start);
+            continue;
+        };
+
+        // the quadratic has constant second differences, so each sample is the previous
+        // one times a ratio that itself changes by a constant factor
+        let step = 1.0 / width as f64;
+        let value = strain(first);
+        let next = strain(interpolate(first, middle, last, step));
+        let after = strain(interpolate(first, middle, last, 2.0 * step));
+        let mut ratio = next / value;
+        let ratio_change = after * value / (next * next);
+        let mut current = value;
+        for _ in 0..width {
+            data.push(current);
+            current *= ratio;
+            ratio *= ratio_change;
+        }
+
+        start += width;
+        first = last;
+    }
+    data.push(strain(first));
+    data.extend((start + 1..n).map(|ii| strain(evaluate(ii))));
+    data.resize(n_total, Complex::new(0.0, 0.0));
+    FrequencySeries::uniform(data, f_lower, delta_f, 0.0, Unit::StrainPerHertz)
+}
+
+// Width in Hz of an interval starting at `frequency` with an interpolation error of about
+// `threshold`. The quadratic through x = 0, w / 2 and w errs by at most
+// |g'''| w^3 / (72 sqrt(3)) for a function g.
+fn band_width<W: Waveform>(model: &W, frequency: f64, threshold: f64) -> f64 {
+    let scale = 72.0 * 3.0f64.sqrt() * threshold;
+    // the phase has derivative 2 pi t(f) and at leading order the time to merger
+    // tau = -t(f) is proportional to f^(-8/3), so the third derivative of the phase is
+    // 2 pi (88 / 9) tau / f^2, largest at the start of the interval
+    let tau = -model.time_of_frequency(frequency);
+    let phase = if tau > 0.0 {
+        (scale * 9.0 * frequency * frequency / (2.0 * PI * 88.0 * tau)).cbrt()
+    } else {
+        f64::INFINITY
+    };
+    // the log amplitude falls as -(7 / 6) ln f, with third derivative -7 / (3 f^3)
+    let amplitude = frequency * (scale * 3.0 / 7.0).cbrt();
+    phase.min(amplitude)
+}
+
+// Quadratic through three equally spaced (log amplitude, phase) pairs at x = 0, 1/2, 1.
+fn interpolate(first: (f64, f64), middle: (f64, f64), last: (f64, f64), x: f64) -> (f64, f64) {
+    let quadratic = |a: f64, b: f64, c: f64| {
+        a + x * (-3.0 * a + 4.0 * b - c) + x * x * (2.0 * a - 4.0 * b + 2.0 * c)
+    };
+    (
+        quadratic(first.0, middle.0, last.0),
+        quadratic(first.1, middle.1, last.1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{imrphenomd::IMRPhenomD, taylor::TaylorF2};
+
+    fn check_against_direct<W: Waveform>(model: &W, f_upper: f64, threshold: f64) {
+        let (f_lower, delta_f) = (20.0, 0.25);
+        let multibanded =
+            multibanded_waveform(model, f_lower, f_upper, delta_f, 0.4, 0.01, threshold);
+        let frequencies: Vec<f64> = (0..multibanded.len())
+            .map(|ii| f_lower + ii as f64 * delta_f)
+            .collect();
+        let direct = model.waveform(&frequencies, 0.4, 0.01);
+        for (exact, approximate) in direct.data.iter().zip(multibanded.data.iter()) {
+            if exact.norm() == 0.0 {
+                assert_eq!(approximate.norm(), 0.0);
+                continue;
+            }
+            assert!((approximate / exact).arg().abs() < 2.0 * threshold);
+            assert!((approximate.norm() / exact.norm() - 1.0).abs() < 2.0 * threshold);
+        }
+    }
+
+    #[test]
+    fn multibanding_matches_direct_evaluation() {
+        // the grid runs past the end of the inspiral model
+        check_against_direct(
+            &TaylorF2::new(20.0, 0.5, 0.3, 0.0, 100.0, 20.0),
+            512.0,
+            1e-3,
+        );
+        check_against_direct(
+            &IMRPhenomD::new(60.0, 0.5, 0.3, 0.0, 100.0, 20.0),
+            1024.0,
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn inspiral_band_width_meets_the_threshold() {
+        let model = TaylorF2::new(20.0, 0.5, 0.3, 0.0, 100.0, 20.0);
+        let threshold = 1e-3;
+        for frequency in [20.0, 40.0, 80.0] {
+            let width = band_width(&model, frequency, threshold);
+            let evaluate = |f: f64| {
+                let v = model.orbital_speed(f);
+                (model.amplitude(v).ln(), model.phase(v, 0.0))
+            };
+            let (first, middle, last) = (
+                evaluate(frequency),
+                evaluate(frequency + width / 2.0),
+                evaluate(frequency + width),
+            );
+            let error = [0.25, 0.75]
+                .iter()
+                .map(|&x| {
+                    let fit = interpolate(first, middle, last, x);
+                    let exact = evaluate(frequency + x * width);
+                    (fit.0 - exact.0).abs().max((fit.1 - exact.1).abs())
+                })
+                .fold(0.0, f64::max);
+            // the estimate is not needlessly narrow either
+            assert!(threshold / 10.0 < error && error < threshold);
+        }
+    }
+}
@@ -0,0 +1,617 @@
+use std::f64::consts::PI;
+use std::fmt::Write;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng, RngExt, SeedableRng};
+
+use crate::{numeric::cholesky, prior::Prior};
+
+const TARGET_ACCEPTANCE: f64 = 0.5;
+// multiples of the walk length a replacement may take before giving up on the walk
+const MAXIMUM_WALK_ROUNDS: usize = 16;
+
+#[derive(Clone, Copy, Debug)]
+struct Point<const N: usize> {
+    unit: [f64; N],
+    ln_likelihood: f64,
+}
+
+// The end of a replacement walk, `None` if no point above the threshold was found.
+struct Walk<const N: usize> {
+    end: Option<Point<N>>,
+    accepted: usize,
+    steps: usize,
+    evaluations: usize,
+}
+
+// Everything needed to continue a run, also the contents of the checkpoint file.
+struct State<const N: usize> {
+    // replacements per iteration, the random numbers depend on it
+    batch: usize,
+    iteration: usize,
+    ln_volume: f64,
+    ln_evidence: f64,
+    information: f64,
+    scale: f64,
+    n_likelihood_evaluations: usize,
+    live: Vec<Point<N>>,
+    // dead points with the log of the prior volume they represent
+    dead: Vec<(Point<N>, f64)>,
+}
+
+impl<const N: usize> State<N> {
+    // Add a point with weight L dX to the evidence and the information
+    // H = int ln(L / Z) L / Z dX, updated as in Skilling (2006).
+    fn accumulate(&mut self, point: Point<N>, ln_width: f64) {
+        let ln_weight = point.ln_likelihood + ln_width;
+        let ln_evidence = log_add_exp(self.ln_evidence, ln_weight);
+        let information = (ln_weight - ln_evidence).exp() * point.ln_likelihood
+            + (self.ln_evidence - ln_evidence).exp() * (self.information + self.ln_evidence)
+            - ln_evidence;
+        // the first update starts from Z = 0
+        self.information = if information.is_finite() {
+            information
+        } else {
+            point.ln_likelihood - ln_evidence
+        };
+        self.ln_evidence = ln_evidence;
+        self.dead.push((point, ln_width));
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "batch {}", self.batch).unwrap();
+        writeln!(text, "iteration {}", self.iteration).unwrap();
+        writeln!(text, "ln_volume {}", self.ln_volume).unwrap();
+        writeln!(text, "ln_evidence {}", self.ln_evidence).unwrap();
+        writeln!(text, "information {}", self.information).unwrap();
+        writeln!(text, "scale {}", self.scale).unwrap();
+        writeln!(
+            text,
+            "n_likelihood_evaluations {}",
+            self.n_likelihood_evaluations
+        )
+        .unwrap();
+        let line = |text: &mut String, point: &Point<N>| {
+            for value in point.unit {
+                write!(text, "{value} ").unwrap();
+            }
+            write!(text, "{}", point.ln_likelihood).unwrap();
+        };
+        writeln!(text, "live {}", self.live.len()).unwrap();
+        for point in self.live.iter() {
+            line(&mut text, point);
+            writeln!(text).unwrap();
+        }
+        writeln!(text, "dead {}", self.dead.len()).unwrap();
+        for (point, ln_width) in self.dead.iter() {
+            line(&mut text, point);
+            writeln!(text, " {ln_width}").unwrap();
+        }
+        text
+    }
+
+    fn from_text(text: &str) -> io::Result<Self> {
+        let mut lines = text.lines();
+        let mut next = || {
+            lines
+                .next()
+                .ok_or_else(|| invalid("checkpoint ended early"))
+        };
+        let field = |line: &str, name: &str| -> io::Result<String> {
+            match line.split_once(' ') {
+                Some((key, value)) if key == name => Ok(value.to_string()),
+                _ => Err(invalid(format!("expected {name} in checkpoint"))),
+            }
+        };
+        fn parse<F: FromStr>(value: &str) -> io::Result<F> {
+            value
+                .parse()
+                .map_err(|_| invalid(format!("invalid number {value:?} in checkpoint")))
+        }
+        let mut state = Self {
+            batch: parse(&field(next()?, "batch")?)?,
+            iteration: parse(&field(next()?, "iteration")?)?,
+            ln_volume: parse(&field(next()?, "ln_volume")?)?,
+            ln_evidence: parse(&field(next()?, "ln_evidence")?)?,
+            information: parse(&field(next()?, "information")?)?,
+            scale: parse(&field(next()?, "scale")?)?,
+            n_likelihood_evaluations: parse(&field(next()?, "n_likelihood_evaluations")?)?,
+            live: Vec::new(),
+            dead: Vec::new(),
+        };
+        let values = |line: &str, length: usize| -> io::Result<Vec<f64>> {
+            let values = line
+                .split_whitespace()
+                .map(parse)
+                .collect::<io::Result<Vec<f64>>>()?;
+            if values.len() != length {
+                return Err(invalid("checkpoint has the wrong dimension"));
+            }
+            Ok(values)
+        };
+        let point = |values: &[f64]| Point {
+            unit: std::array::from_fn(|ii| values[ii]),
+            ln_likelihood: values[N],
+        };
+        let n_live: usize = parse(&field(next()?, "live")?)?;
+        for _ in 0..n_live {
+            state.live.push(point(&values(next()?, N + 1)?));
+        }
+        let n_dead: usize = parse(&field(next()?, "dead")?)?;
+        for _ in 0..n_dead {
+            let values = values(next()?, N + 2)?;
+            state.dead.push((point(&values), values[N + 1]));
+        }
+        Ok(state)
+    }
+}
+
+fn invalid<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn invalid_input(error: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+// Box-Muller transform
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let radius = (-2.0 * (1.0 - rng.random::<f64>()).ln()).sqrt();
+    radius * (2.0 * PI * rng.random::<f64>()).cos()
+}
+
+fn log_add_exp(a: f64, b: f64) -> f64 {
+    if a == f64::NEG_INFINITY {
+        return b;
+    }
+    if b == f64::NEG_INFINITY {
+        return a;
+    }
+    a.max(b) + (-(a - b).abs()).exp().ln_1p()
+}
+
+/// Nested sampling over the unit hypercube of a `Prior`, Skilling (2006).
+///
+/// Each iteration removes the `n_threads` lowest likelihood live points, accounting for
+/// the prior volume one point at a time, and replaces them in parallel by random walks
+/// of at least `walks` steps from distinct surviving live points constrained to the
+/// highest removed likelihood. A walk continues with shorter steps until it moves, for up
+/// to 16 times its length, then falls back to as many draws from the prior. The
+/// Gaussian steps follow the covariance of the live points and their size adapts to an
+/// acceptance fraction of one half. The run stops when the live points could change the
+/// log evidence by less than `dlogz`.
+///
+/// With a `checkpoint` path the state is written there as plain text every
+/// `checkpoint_interval` iterations and a run resumes from it if it exists. The random
+/// numbers for each iteration are seeded from `seed`, the iteration and the thread, so
+/// runs reproduce each other, resumed or not, only with the same `n_threads`. It defaults
+/// to the available parallelism, set it explicitly for results that reproduce across
+/// machines. The checkpoint records it and resuming with another value is an error.
+#[derive(Clone, Debug)]
+pub struct NestedSampler<P, L> {
+    pub prior: P,
+    pub log_likelihood: L,
+    pub n_live: usize,
+    pub walks: usize,
+    pub n_threads: usize,
+    pub dlogz: f64,
+    pub seed: u64,
+    pub checkpoint: Option<PathBuf>,
+    pub checkpoint_interval: usize,
+}
+
+/// Dead points followed by the final live points, with their posterior weights.
+#[derive(Clone, Debug)]
+pub struct NestedSamplingResult<const N: usize> {
+    pub samples: Vec<[f64; N]>,
+    pub ln_likelihood: Vec<f64>,
+    /// Normalised log posterior weights.
+    pub ln_weights: Vec<f64>,
+    pub ln_evidence: f64,
+    pub ln_evidence_error: f64,
+    /// Kullback-Leibler divergence from the prior to the posterior in nats.
+    pub information: f64,
+    pub n_likelihood_evaluations: usize,
+}
+
+impl<const N: usize> NestedSamplingResult<N> {
+    /// Equally weighted posterior samples by rejection.
+    pub fn posterior_samples<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<[f64; N]> {
+        let maximum = self
+            .ln_weights
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        self.samples
+            .iter()
+            .zip(self.ln_weights.iter())
+            .filter(|(_, &ln_weight)| rng.random::<f64>() < (ln_weight - maximum).exp())
+            .map(|(&sample, _)| sample)
+            .collect()
+    }
+}
+
+impl<P, L> NestedSampler<P, L> {
+    pub fn new(prior: P, log_likelihood: L, n_live: usize) -> Self {
+        assert!(n_live > 1, "need at least two live points");
+        Self {
+            prior,
+            log_likelihood,
+            n_live,
+            walks: 32,
+            n_threads: std::thread::available_parallelism()
+                .map(|threads| threads.get())
+                .unwrap_or(1),
+            dlogz: 0.1,
+            seed: 0,
+            checkpoint: None,
+            checkpoint_interval: 1000,
+        }
+    }
+
+    fn rng(&self, iteration: usize, stream: usize) -> StdRng {
+        StdRng::seed_from_u64(
+            self.seed ^ (iteration as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ stream as u64,
+        )
+    }
+
+    // one replacement per thread, keeping at least one live point to start walks from
+    fn batch(&self) -> usize {
+        self.n_threads.clamp(1, self.n_live - 1)
+    }
+
+    /// Run to convergence. Fails if the settings are invalid, if the checkpoint cannot be
+    /// read or written, or if no replacement above the likelihood threshold can be found,
+    /// as on a likelihood plateau.
+    pub fn run<const N: usize>(&self) -> io::Result<NestedSamplingResult<N>>
+    where
+        P: Prior<N> + Sync,
+        L: Fn(&[f64; N]) -> f64 + Sync,
+    {
+        if self.walks == 0 {
+            return Err(invalid_input("walks must be positive"));
+        }
+        if self.n_live < 2 {
+            return Err(invalid_input("need at least two live points"));
+        }
+        if self.checkpoint.is_some() && self.checkpoint_interval == 0 {
+            return Err(invalid_input("checkpoint interval must be positive"));
+        }
+        let ln_likelihood = |unit: &[f64; N]| (self.log_likelihood)(&self.prior.rescale(*unit));
+        let batch = self.batch();
+        let mut state = match &self.checkpoint {
+            Some(path) if path.exists() => {
+                let state = State::from_text(&std::fs::read_to_string(path)?)?;
+                if state.live.len() != self.n_live {
+                    return Err(invalid("checkpoint has a different number of live points"));
+                }
+                if state.batch != batch {
+                    return Err(invalid(format!(
+                        "checkpoint replaces {} points per iteration, set n_threads to match",
+                        state.batch
+                    )));
+                }
+                state
+            }
+            _ => self.initial_state(),
+        };
+
+        loop {
+            let ln_remaining = state
+                .live
+                .iter()
+                .map(|point| point.ln_likelihood)
+                .fold(f64::NEG_INFINITY, f64::max)
+                + state.ln_volume;
+            if log_add_exp(state.ln_evidence, ln_remaining) - state.ln_evidence < self.dlogz {
+                break;
+            }
+
+            state
+                .live
+                .sort_by(|a, b| a.ln_likelihood.total_cmp(&b.ln_likelihood));
+            let survivors = state.live.split_off(batch);
+            let removed = std::mem::replace(&mut state.live, survivors);
+            let threshold = removed[batch - 1].ln_likelihood;
+            for (ii, point) in removed.into_iter().enumerate() {
+                // the live set shrinks by one for each point removed in the batch
+                let shrinkage = 1.0 / (self.n_live - ii) as f64;
+                let ln_width = state.ln_volume + (-(-shrinkage).exp_m1()).ln();
+                state.ln_volume -= shrinkage;
+                state.accumulate(point, ln_width);
+            }
+
+            // steps are shaped by the covariance of the live points
+            let n_points = state.live.len() as f64;
+            let mean: [f64; N] = std::array::from_fn(|jj| {
+                state.live.iter().map(|point| point.unit[jj]).sum::<f64>() / n_points
+            });
+            let covariance: [[f64; N]; N] = std::array::from_fn(|ii| {
+                std::array::from_fn(|jj| {
+                    let value = state
+                        .live
+                        .iter()
+                        .map(|point| (point.unit[ii] - mean[ii]) * (point.unit[jj] - mean[jj]))
+                        .sum::<f64>()
+                        / n_points;
+                    // keep the factorisation well defined when the live points collapse
+                    if ii == jj {
+                        value + 1e-20
+                    } else {
+                        value
+                    }
+                })
+            });
+            let factor = cholesky(&covariance);
+            let live = &state.live;
+            let scale = state.scale;
+            let iteration = state.iteration;
+            // each walk starts from a different survivor, drawn without replacement
+            let mut starts: Vec<usize> = (0..live.len()).collect();
+            let mut rng = self.rng(iteration, batch);
+            for ii in 0..batch {
+                let jj = rng.random_range(ii..starts.len());
+                starts.swap(ii, jj);
+            }
+            let walks: Vec<Walk<N>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = starts[..batch]
+                    .iter()
+                    .enumerate()
+                    .map(|(stream, &start)| {
+                        let ln_likelihood = &ln_likelihood;
+                        scope.spawn(move || {
+                            let mut rng = self.rng(iteration, stream);
+                            let mut current = live[start];
+                            let mut scale = scale;
+                            let (mut accepted, mut steps, mut evaluations) = (0, 0, 0);
+                            // a walk that never moves would duplicate its starting point, so
+                            // keep going with shorter steps until one is accepted
+                            while steps < self.walks
+                                || (accepted == 0 && steps < MAXIMUM_WALK_ROUNDS * self.walks)
+                            {
+                                if steps >= self.walks && steps % self.walks == 0 {
+                                    scale /= 2.0;
+                                }
+                                steps += 1;
+                                let normal: [f64; N] =
+                                    std::array::from_fn(|_| standard_normal(&mut rng));
+                                let unit: [f64; N] = std::array::from_fn(|ii| {
+                                    current.unit[ii]
+                                        + scale
+                                            * (0..=ii)
+                                                .map(|jj| factor[ii][jj] * normal[jj])
+                                                .sum::<f64>()
+                                });
+                                if unit.iter().any(|value| !(0.0..=1.0).contains(value)) {
+                                    continue;
+                                }
+                                let value = ln_likelihood(&unit);
+                                evaluations += 1;
+                                if value > threshold {
+                                    current = Point {
+                                        unit,
+                                        ln_likelihood: value,
+                                    };
+                                    accepted += 1;
+                                }
+                            }
+                            let mut end = (accepted > 0).then_some(current);
+                            // a stuck walk, for instance in a narrow mode, is replaced by
+                            // rejection sampling from the prior
+                            for _ in 0..MAXIMUM_WALK_ROUNDS * self.walks {
+                                if end.is_some() {
+                                    break;
+                                }
+                                let unit: [f64; N] = std::array::from_fn(|_| rng.random());
+                                let value = ln_likelihood(&unit);
+                                evaluations += 1;
+                                if value > threshold {
+                                    end = Some(Point {
+                                        unit,
+                                        ln_likelihood: value,
+                                    });
+                                }
+                            }
+                            Walk {
+                                end,
+                                accepted,
+                                steps,
+                                evaluations,
+                            }
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect()
+            });
+            let accepted: usize = walks.iter().map(|walk| walk.accepted).sum();
+            let steps: usize = walks.iter().map(|walk| walk.steps).sum();
+            let acceptance = accepted as f64 / steps as f64;
+            state.scale *= ((acceptance - TARGET_ACCEPTANCE) / TARGET_ACCEPTANCE / N as f64).exp();
+            state.n_likelihood_evaluations +=
+                walks.iter().map(|walk| walk.evaluations).sum::<usize>();
+            for walk in walks {
+                let point = walk.end.ok_or_else(|| {
+                    io::Error::other(format!(
+                        "no point found above the likelihood threshold {threshold}, the \
+                         likelihood may be flat there"
+                    ))
+                })?;
+                state.live.push(point);
+            }
+
+            state.iteration += 1;
+            if let Some(path) = &self.checkpoint {
+                if state.iteration % self.checkpoint_interval == 0 {
+                    // write then rename so an interrupted write leaves the old checkpoint
+                    let temporary = path.with_extension("tmp");
+                    std::fs::write(&temporary, state.to_text())?;
+                    std::fs::rename(&temporary, path)?;
+                }
+            }
+        }
+
+        // the remaining prior volume is shared equally by the live points
+        let ln_width = state.ln_volume - (self.n_live as f64).ln();
+        state
+            .live
+            .sort_by(|a, b| a.ln_likelihood.total_cmp(&b.ln_likelihood));
+        for point in std::mem::take(&mut state.live) {
+            state.accumulate(point, ln_width);
+        }
+        Ok(NestedSamplingResult {
+            samples: state
+                .dead
+                .iter()
+                .map(|(point, _)| self.prior.rescale(point.unit))
+                .collect(),
+            ln_likelihood: state
+                .dead
+                .iter()
+                .map(|(point, _)| point.ln_likelihood)
+                .collect(),
+            ln_weights: state
+                .dead
+                .iter()
+                .map(|(point, ln_width)| point.ln_likelihood + ln_width - state.ln_evidence)
+                .collect(),
+            ln_evidence: state.ln_evidence,
+            ln_evidence_error: (state.information.max(0.0) / self.n_live as f64).sqrt(),
+            information: state.information,
+            n_likelihood_evaluations: state.n_likelihood_evaluations,
+        })
+    }
+
+    fn initial_state<const N: usize>(&self) -> State<N>
+    where
+        P: Prior<N> + Sync,
+        L: Fn(&[f64; N]) -> f64 + Sync,
+    {
+        let n_threads = self.n_threads.max(1);
+        let chunk = self.n_live.div_ceil(n_threads);
+        let live = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..n_threads)
+                .map(|stream| {
+                    scope.spawn(move || {
+                        let mut rng = self.rng(usize::MAX, stream);
+                        let start = (stream * chunk).min(self.n_live);
+                        let end = ((stream + 1) * chunk).min(self.n_live);
+                        (start..end)
+                            .map(|_| {
+                                let unit: [f64; N] = std::array::from_fn(|_| rng.random());
+                                Point {
+                                    unit,
+                                    ln_likelihood: (self.log_likelihood)(&self.prior.rescale(unit)),
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        State {
+            batch: self.batch(),
+            iteration: 0,
+            ln_volume: 0.0,
+            ln_evidence: f64::NEG_INFINITY,
+            information: 0.0,
+            scale: 1.0,
+            n_likelihood_evaluations: self.n_live,
+            live,
+            dead: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // uniform on the square [-5, 5]^2
+    struct Square;
+
+    impl Prior<2> for Square {
+        fn rescale(&self, unit: [f64; 2]) -> [f64; 2] {
+            unit.map(|value| 10.0 * value - 5.0)
+        }
+
+        fn ln_prob(&self, _: &[f64; 2]) -> f64 {
+            -100.0f64.ln()
+        }
+    }
+
+    // normalised with width 0.5, so the evidence is 1 / 100
+    fn gaussian(point: &[f64; 2]) -> f64 {
+        let width: f64 = 0.5;
+        -(point[0].powi(2) + point[1].powi(2)) / (2.0 * width.powi(2))
+            - (2.0 * PI * width.powi(2)).ln()
+    }
+
+    fn sampler<L>(log_likelihood: L) -> NestedSampler<Square, L> {
+        let mut sampler = NestedSampler::new(Square, log_likelihood, 200);
+        sampler.n_threads = 2;
+        sampler.walks = 16;
+        sampler
+    }
+
+    #[test]
+    fn evidence_of_a_gaussian() {
+        let result = sampler(gaussian).run().unwrap();
+        let expected = -100.0f64.ln();
+        assert!((result.ln_evidence - expected).abs() < 3.0 * result.ln_evidence_error);
+        // the weights are normalised and the information is ln(100 / 2 pi e width^2)
+        let total: f64 = result
+            .ln_weights
+            .iter()
+            .map(|ln_weight| ln_weight.exp())
+            .sum();
+        assert!((total - 1.0).abs() < 1e-12);
+        let information = (100.0 / (2.0 * PI * 0.25)).ln() - 1.0;
+        assert!((result.information - information).abs() < 0.3);
+        assert!(result.n_likelihood_evaluations >= result.samples.len());
+    }
+
+    #[test]
+    fn resumed_runs_reproduce() {
+        let path = std::env::temp_dir().join("waveformrs_nested_sampling_test_checkpoint.txt");
+        let _ = std::fs::remove_file(&path);
+        let mut sampler = sampler(gaussian);
+        let uninterrupted = sampler.run().unwrap();
+        sampler.checkpoint = Some(path.clone());
+        sampler.checkpoint_interval = 100;
+        sampler.run().unwrap();
+        let resumed = sampler.run().unwrap();
+        sampler.n_threads = 3;
+        let error = sampler.run().unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resumed.samples, uninterrupted.samples);
+        assert_eq!(resumed.ln_evidence, uninterrupted.ln_evidence);
+        assert_eq!(
+            resumed.n_likelihood_evaluations,
+            uninterrupted.n_likelihood_evaluations
+        );
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn invalid_settings_and_plateaus_are_errors() {
+        let mut walkless = sampler(gaussian);
+        walkless.walks = 0;
+        assert_eq!(
+            walkless.run().unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        // nothing is ever above the threshold of a constant likelihood
+        let flat = sampler(|_: &[f64; 2]| 0.0);
+        assert_eq!(flat.run().unwrap_err().kind(), io::ErrorKind::Other);
+    }
+}
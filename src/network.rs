@@ -0,0 +1,129 @@
+use std::f64::consts::PI;
+
+use autodiff::FT;
+use num_complex::Complex;
+
+use crate::{
+    constants::MPC_SI,
+    detector::Detector,
+    numeric::cast,
+    parameters::{BinaryParameters, ExtrinsicParameters},
+    series::{FrequencySeries, Unit},
+    waveform::{Waveform, WaveformGenerator},
+};
+
+#[derive(Clone, Debug)]
+pub struct Network {
+    pub detectors: Vec<Detector>,
+}
+
+impl Network {
+    pub fn new(detectors: Vec<Detector>) -> Self {
+        Self { detectors }
+    }
+
+    // None if any name is not a known detector
+    pub fn from_names(names: &[&str]) -> Option<Self> {
+        names
+            .iter()
+            .map(|name| Detector::from_name(name))
+            .collect::<Option<Vec<Detector>>>()
+            .map(Self::new)
+    }
+
+    pub fn len(&self) -> usize {
+        self.detectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.detectors.is_empty()
+    }
+
+    /// Strain in each detector with Fourier phases referenced to `epoch`.
+    pub fn strain<W: Waveform>(
+        &self,
+        model: &W,
+        frequencies: &[f64],
+        parameters: &ExtrinsicParameters,
+        epoch: f64,
+    ) -> Vec<FrequencySeries> {
+        let scale = model.luminosity_distance() / (parameters.luminosity_distance * MPC_SI);
+        let (plus, cross) =
+            model.waveform_modes(frequencies, parameters.phi_c, 0.0, parameters.theta_jn);
+        let (plus, cross) = (&plus * scale, &cross * scale);
+        self.detectors
+            .iter()
+            .map(|detector| detector.project(&plus, &cross, parameters, epoch))
+            .collect()
+    }
+
+    /// Strain in each detector and its derivatives with respect to each of the
+    /// `BINARY_PARAMETERS`, indexed as `[detector][parameter]`.
+    ///
+    /// `generator` builds the waveform from dual-number parameters with one forward pass
+    /// per parameter, so every stage, including the antenna patterns and time delays, is
+    /// differentiated. The luminosity distance is rescaled as in `strain`.
+    pub fn strain_gradient<G: WaveformGenerator<FT<f64>>>(
+        &self,
+        generator: &G,
+        frequencies: &[f64],
+        parameters: &BinaryParameters,
+        epoch: f64,
+    ) -> (Vec<FrequencySeries>, Vec<Vec<FrequencySeries>>) {
+        let values = parameters.to_array();
+        let mut strains = vec![vec![Complex::new(0.0, 0.0); frequencies.len()]; self.len()];
+        let mut derivatives =
+            vec![vec![vec![Complex::new(0.0, 0.0); frequencies.len()]; values.len()]; self.len()];
+        for index in 0..values.len() {
+            let dual = BinaryParameters::from_array(std::array::from_fn(|ii| {
+                if ii == index {
+                    FT::var(values[ii])
+                } else {
+                    FT::cst(values[ii])
+                }
+            }));
+            let waveform = generator.generate(&dual);
+            let scale = waveform.luminosity_distance()
+                / (dual.luminosity_distance * cast::<FT<f64>>(MPC_SI));
+            let modes: Vec<_> = frequencies
+                .iter()
+                .map(|&frequency| {
+                    let (plus, cross) = waveform.modes_single_frequency(
+                        FT::cst(frequency),
+                        dual.phi_c,
+                        FT::cst(0.0),
+                        dual.theta_jn,
+                    );
+                    (plus * scale, cross * scale)
+                })
+                .collect();
+            for (detector, (values, derivatives)) in self
+                .detectors
+                .iter()
+                .zip(strains.iter_mut().zip(derivatives.iter_mut()))
+            {
+                let (f_plus, f_cross, delay) =
+                    detector.response(dual.ra, dual.dec, dual.psi, dual.geocent_time);
+                let delay = dual.geocent_time - epoch + delay;
+                for (ii, (&frequency, &(plus, cross))) in
+                    frequencies.iter().zip(modes.iter()).enumerate()
+                {
+                    let signal = (plus * f_plus + cross * f_cross)
+                        * Complex::from_polar(FT::cst(1.0), delay * (-2.0 * PI * frequency));
+                    values[ii] = Complex::new(signal.re.value(), signal.im.value());
+                    derivatives[index][ii] = Complex::new(signal.re.deriv(), signal.im.deriv());
+                }
+            }
+        }
+        let series = |data: Vec<Complex<f64>>| {
+            FrequencySeries::new(data, frequencies.to_vec(), epoch, Unit::StrainPerHertz)
+        };
+        (
+            strains.into_iter().map(series).collect(),
+            derivatives
+                .into_iter()
+                .map(|detector| detector.into_iter().map(series).collect())
+                .collect(),
+        )
+    }
+}
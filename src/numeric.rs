@@ -0,0 +1,138 @@
+use std::fmt::Debug;
+use std::iter::Sum;
+use std::ops::{AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+use num_traits::{Float, FloatConst, FromPrimitive};
+
+/// Scalar types the waveform models can be evaluated with.
+///
+/// This covers f64, f32 for speed, the forward-mode dual numbers from `autodiff` for
+/// derivatives and `Copy` types with more precision than f64 for validation. The fitted
+/// IMRPhenomD coefficients and the physical constants are only known to f64 precision.
+pub trait Real:
+    Float + FloatConst + FromPrimitive + AddAssign + SubAssign + MulAssign + DivAssign + Sum + Debug
+{
+}
+
+impl<T> Real for T where
+    T: Float
+        + FloatConst
+        + FromPrimitive
+        + AddAssign
+        + SubAssign
+        + MulAssign
+        + DivAssign
+        + Sum
+        + Debug
+{
+}
+
+// Literals in the model code are written as f64 and converted to the working type,
+// fractions are written as c(a) / c(b) so they are exact in more precise types.
+pub(crate) fn cast<T: Real>(value: f64) -> T {
+    T::from_f64(value).unwrap()
+}
+
+/// Solve a small dense linear system by Gaussian elimination with partial pivoting.
+///
+/// The scalars may be real or complex, `magnitude` orders the candidate pivots, `abs` for
+/// real and `norm` for complex scalars. The rows are fixed-size arrays or vectors and the
+/// solution is returned in place of `vector`.
+pub(crate) fn solve<S, R, V, M>(
+    mut matrix: impl AsMut<[R]>,
+    mut vector: V,
+    magnitude: impl Fn(S) -> M,
+) -> V
+where
+    S: Copy + Sub<Output = S> + Mul<Output = S> + Div<Output = S> + SubAssign,
+    R: AsRef<[S]> + AsMut<[S]>,
+    V: AsMut<[S]>,
+    M: PartialOrd,
+{
+    let matrix = matrix.as_mut();
+    let values = vector.as_mut();
+    let n = values.len();
+    assert_eq!(matrix.len(), n, "matrix and vector have different sizes");
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&a, &b| {
+                magnitude(matrix[a].as_ref()[column])
+                    .partial_cmp(&magnitude(matrix[b].as_ref()[column]))
+                    .unwrap()
+            })
+            .unwrap();
+        matrix.swap(column, pivot);
+        values.swap(column, pivot);
+        let (upper, lower) = matrix.split_at_mut(column + 1);
+        let row = upper[column].as_ref();
+        let (solved, remaining) = values.split_at_mut(column + 1);
+        for (other, value) in lower.iter_mut().zip(remaining.iter_mut()) {
+            let other = other.as_mut();
+            let factor = other[column] / row[column];
+            for (entry, &pivot_entry) in other.iter_mut().zip(row.iter()).skip(column) {
+                *entry -= factor * pivot_entry;
+            }
+            *value -= factor * solved[column];
+        }
+    }
+    for ii in (0..n).rev() {
+        let row = matrix[ii].as_ref();
+        let mut value = values[ii];
+        for jj in ii + 1..n {
+            value -= row[jj] * values[jj];
+        }
+        values[ii] = value / row[ii];
+    }
+    vector
+}
+
+/// Lower triangular factor of a symmetric positive definite matrix.
+pub(crate) fn cholesky<const N: usize>(matrix: &[[f64; N]; N]) -> [[f64; N]; N] {
+    let mut factor = [[0.0; N]; N];
+    for ii in 0..N {
+        for jj in 0..=ii {
+            let value = matrix[ii][jj]
+                - (0..jj)
+                    .map(|kk| factor[ii][kk] * factor[jj][kk])
+                    .sum::<f64>();
+            factor[ii][jj] = if ii == jj {
+                value.max(0.0).sqrt()
+            } else {
+                value / factor[jj][jj]
+            };
+        }
+    }
+    factor
+}
+
+#[cfg(test)]
+mod tests {
+    use num_complex::Complex;
+
+    use super::*;
+
+    #[test]
+    fn solve_needs_pivoting_for_a_zero_leading_entry() {
+        let matrix = [[0.0, 2.0, 1.0], [1.0, 1.0, 0.0], [3.0, 0.0, 1.0]];
+        let solution = solve(matrix, [5.0, 3.0, 4.0], f64::abs);
+        for (value, expected) in solution.iter().zip([1.0, 2.0, 1.0]) {
+            assert!((value - expected).abs() < 1e-14);
+        }
+    }
+
+    #[test]
+    fn solve_complex_system() {
+        let i = Complex::new(0.0, 1.0);
+        let one = Complex::new(1.0, 0.0);
+        let matrix = vec![vec![one, i], vec![i, 2.0 * one]];
+        let expected = [Complex::new(1.0, -1.0), Complex::new(0.5, 2.0)];
+        let vector: Vec<Complex<f64>> = matrix
+            .iter()
+            .map(|row| row.iter().zip(expected.iter()).map(|(a, b)| a * b).sum())
+            .collect();
+        let solution = solve(matrix, vector, |value: Complex<f64>| value.norm());
+        for (value, expected) in solution.iter().zip(expected.iter()) {
+            assert!((value - expected).norm() < 1e-14);
+        }
+    }
+}
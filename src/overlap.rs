@@ -0,0 +1,261 @@
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+
+use num_complex::Complex;
+
+use crate::{
+    fft::ifft,
+    inner_product::{inner_product, trapezoid_weights},
+    psd::PowerSpectralDensity,
+    series::FrequencySeries,
+    waveform::Waveform,
+};
+
+const TIME_OVERSAMPLING: f64 = 4.0;
+const POLARISATION_GRID: usize = 16;
+const GOLDEN_SECTION_ITERATIONS: usize = 40;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchError {
+    /// The frequencies are not uniformly spaced so the time shifts cannot use an FFT.
+    NonUniformFrequencies,
+    /// The two series are defined on different frequencies.
+    DifferentFrequencies,
+    /// The PSD does not have one value per frequency.
+    PsdLength { expected: usize, found: usize },
+    /// One of the series has zero norm so the overlap cannot be normalised.
+    ZeroNorm,
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NonUniformFrequencies => write!(f, "match requires uniformly spaced frequencies"),
+            Self::DifferentFrequencies => {
+                write!(f, "frequency series are defined on different frequencies")
+            }
+            Self::PsdLength { expected, found } => {
+                write!(f, "psd has {found} values for {expected} frequencies")
+            }
+            Self::ZeroNorm => write!(f, "cannot normalise the overlap of a zero series"),
+        }
+    }
+}
+
+impl Error for MatchError {}
+
+/// Normalised overlap maximised over a relative phase and time shift.
+///
+/// Both series must share a uniform frequency grid, the time shifts are searched with an
+/// inverse FFT of the integrand and the peak is refined with a parabola.
+pub fn match_series(
+    a: &FrequencySeries,
+    b: &FrequencySeries,
+    psd: &[f64],
+) -> Result<f64, MatchError> {
+    let delta_f = a.delta_f().ok_or(MatchError::NonUniformFrequencies)?;
+    if !a.same_frequencies(b) {
+        return Err(MatchError::DifferentFrequencies);
+    }
+    if psd.len() != a.len() {
+        return Err(MatchError::PsdLength {
+            expected: a.len(),
+            found: psd.len(),
+        });
+    }
+    let norm = (inner_product(a, a, psd) * inner_product(b, b, psd)).sqrt();
+    if norm == 0.0 || norm.is_nan() {
+        return Err(MatchError::ZeroNorm);
+    }
+    let weights = trapezoid_weights(a.frequencies());
+    let length = (a.len() as f64)
+        .max(TIME_OVERSAMPLING * a.f_upper().unwrap_or(0.0) / delta_f)
+        .ceil() as usize;
+    let mut integrand = vec![Complex::new(0.0, 0.0); length.next_power_of_two()];
+    for ii in 0..a.len() {
+        integrand[ii] = 4.0 * a.data[ii] * b.data[ii].conj() * weights[ii] / psd[ii];
+    }
+    let overlaps: Vec<f64> = ifft(&integrand).iter().map(|value| value.norm()).collect();
+    let n = overlaps.len();
+    let (peak, &centre) = overlaps
+        .iter()
+        .enumerate()
+        .max_by(|x, y| x.1.total_cmp(y.1))
+        .unwrap();
+    let before = overlaps[(peak + n - 1) % n];
+    let after = overlaps[(peak + 1) % n];
+    let curvature = before - 2.0 * centre + after;
+    let refined = if curvature < 0.0 {
+        centre - (after - before).powi(2) / (8.0 * curvature)
+    } else {
+        centre
+    };
+    Ok(refined / norm)
+}
+
+/// Match additionally maximised over the polarisation of the template, `plus` and
+/// `cross` are combined as cos(kappa) plus + sin(kappa) cross.
+pub fn polarisation_match(
+    signal: &FrequencySeries,
+    plus: &FrequencySeries,
+    cross: &FrequencySeries,
+    psd: &[f64],
+) -> Result<f64, MatchError> {
+    if !plus.same_frequencies(cross) {
+        return Err(MatchError::DifferentFrequencies);
+    }
+    let evaluate = |kappa: f64| {
+        let template = &(plus * kappa.cos()) + &(cross * kappa.sin());
+        match_series(signal, &template, psd)
+    };
+    let step = PI / POLARISATION_GRID as f64;
+    let mut grid = Vec::with_capacity(POLARISATION_GRID);
+    for ii in 0..POLARISATION_GRID {
+        let kappa = ii as f64 * step;
+        grid.push((kappa, evaluate(kappa)?));
+    }
+    let (best, best_match) = grid.into_iter().max_by(|x, y| x.1.total_cmp(&y.1)).unwrap();
+    // golden section search keeping one interior point from the previous iteration
+    let ratio = (5.0f64.sqrt() - 1.0) / 2.0;
+    let (mut lower, mut upper) = (best - step, best + step);
+    let mut left = upper - ratio * (upper - lower);
+    let mut right = lower + ratio * (upper - lower);
+    let (mut left_match, mut right_match) = (evaluate(left)?, evaluate(right)?);
+    for _ in 0..GOLDEN_SECTION_ITERATIONS {
+        if left_match > right_match {
+            upper = right;
+            (right, right_match) = (left, left_match);
+            left = upper - ratio * (upper - lower);
+            left_match = evaluate(left)?;
+        } else {
+            lower = left;
+            (left, left_match) = (right, right_match);
+            right = lower + ratio * (upper - lower);
+            right_match = evaluate(right)?;
+        }
+    }
+    Ok(evaluate((lower + upper) / 2.0)?.max(best_match))
+}
+
+fn uniform_frequencies(f_lower: f64, f_upper: f64, delta_f: f64) -> Vec<f64> {
+    let n = ((f_upper - f_lower) / delta_f).floor() as usize + 1;
+    (0..n).map(|ii| f_lower + ii as f64 * delta_f).collect()
+}
+
+pub fn waveform_match<A: Waveform, B: Waveform>(
+    a: &A,
+    b: &B,
+    psd: &PowerSpectralDensity,
+    f_lower: f64,
+    f_upper: f64,
+    delta_f: f64,
+) -> Result<f64, MatchError> {
+    let frequencies = uniform_frequencies(f_lower, f_upper, delta_f);
+    match_series(
+        &a.waveform(&frequencies, 0.0, 0.0),
+        &b.waveform(&frequencies, 0.0, 0.0),
+        &psd.evaluate(&frequencies),
+    )
+}
+
+pub fn waveform_mismatch<A: Waveform, B: Waveform>(
+    a: &A,
+    b: &B,
+    psd: &PowerSpectralDensity,
+    f_lower: f64,
+    f_upper: f64,
+    delta_f: f64,
+) -> Result<f64, MatchError> {
+    Ok(1.0 - waveform_match(a, b, psd, f_lower, f_upper, delta_f)?)
+}
+
+/// Match between the plus polarisation of `a` and `b` maximised over the polarisation of
+/// `b`, both at inclination `theta_jn`.
+pub fn waveform_polarisation_match<A: Waveform, B: Waveform>(
+    a: &A,
+    b: &B,
+    psd: &PowerSpectralDensity,
+    f_lower: f64,
+    f_upper: f64,
+    delta_f: f64,
+    theta_jn: f64,
+) -> Result<f64, MatchError> {
+    let frequencies = uniform_frequencies(f_lower, f_upper, delta_f);
+    let (signal, _) = a.waveform_modes(&frequencies, 0.0, 0.0, theta_jn);
+    let (plus, cross) = b.waveform_modes(&frequencies, 0.0, 0.0, theta_jn);
+    polarisation_match(&signal, &plus, &cross, &psd.evaluate(&frequencies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{series::Unit, taylor::TaylorF2};
+
+    fn chirp(f_lower: f64, delta_f: f64, phase: f64, time: f64) -> FrequencySeries {
+        let data = (0..400)
+            .map(|ii| {
+                let f = f_lower + ii as f64 * delta_f;
+                let psi = 2.0 * PI * f * time + phase + 3e4 * f.powf(-5.0 / 3.0);
+                Complex::from_polar(f.powf(-7.0 / 6.0), -psi)
+            })
+            .collect();
+        FrequencySeries::uniform(data, f_lower, delta_f, 0.0, Unit::StrainPerHertz)
+    }
+
+    #[test]
+    fn match_is_invariant_to_phase_and_time_shifts() {
+        let a = chirp(20.0, 0.25, 0.0, 0.0);
+        let b = chirp(20.0, 0.25, 1.3, 0.01);
+        let psd = vec![1.0; a.len()];
+        assert!((match_series(&a, &a, &psd).unwrap() - 1.0).abs() < 1e-10);
+        assert!((match_series(&a, &b, &psd).unwrap() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn match_is_below_one_for_different_signals() {
+        let a = chirp(20.0, 0.25, 0.0, 0.0);
+        let b = a.map(|value| value * value.norm());
+        let psd = vec![1.0; a.len()];
+        assert!(match_series(&a, &b, &psd).unwrap() < 0.99);
+    }
+
+    #[test]
+    fn match_rejects_inconsistent_inputs() {
+        let a = chirp(20.0, 0.25, 0.0, 0.0);
+        let psd = vec![1.0; a.len()];
+        assert_eq!(
+            match_series(&a, &chirp(20.0, 0.5, 0.0, 0.0), &psd),
+            Err(MatchError::DifferentFrequencies)
+        );
+        assert_eq!(
+            match_series(&a, &a, &psd[1..]),
+            Err(MatchError::PsdLength {
+                expected: a.len(),
+                found: a.len() - 1
+            })
+        );
+        assert_eq!(
+            match_series(&a, &(&a * 0.0), &psd),
+            Err(MatchError::ZeroNorm)
+        );
+    }
+
+    #[test]
+    fn polarisation_match_recovers_the_template_polarisation() {
+        let plus = chirp(20.0, 0.25, 0.0, 0.0);
+        let cross = chirp(20.0, 0.25, PI / 2.0, 0.0);
+        let signal = &(&plus * 0.6) + &(&cross * 0.8);
+        let psd = vec![1.0; plus.len()];
+        let value = polarisation_match(&signal, &plus, &cross, &psd).unwrap();
+        assert!((value - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn waveform_match_with_itself_is_one() {
+        let model = TaylorF2::new(20.0, 0.5, 0.0, 0.0, 500.0, 20.0);
+        let psd = PowerSpectralDensity::AdvancedLIGO;
+        let mismatch = waveform_mismatch(&model, &model, &psd, 20.0, 256.0, 0.25).unwrap();
+        assert!(mismatch.abs() < 1e-10);
+    }
+}
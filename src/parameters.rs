@@ -0,0 +1,98 @@
+use crate::numeric::Real;
+
+/// Parameters describing the source orientation and location relative to the geocentre.
+///
+/// Angles are in radians, `geocent_time` is the GPS time of coalescence at the geocentre
+/// and `luminosity_distance` is in Mpc.
+#[derive(Clone, Copy, Debug)]
+pub struct ExtrinsicParameters {
+    pub ra: f64,
+    pub dec: f64,
+    pub psi: f64,
+    pub theta_jn: f64,
+    pub phi_c: f64,
+    pub geocent_time: f64,
+    pub luminosity_distance: f64,
+}
+
+/// Order of the `BinaryParameters` fields in arrays and gradients.
+pub const BINARY_PARAMETERS: [&str; 11] = [
+    "total_mass",
+    "mass_ratio",
+    "chi_1",
+    "chi_2",
+    "ra",
+    "dec",
+    "psi",
+    "theta_jn",
+    "phi_c",
+    "geocent_time",
+    "luminosity_distance",
+];
+
+/// Full parameter set of an aligned-spin binary, masses are detector-frame solar masses
+/// and `mass_ratio` is m2 / m1 <= 1.
+#[derive(Clone, Copy, Debug)]
+pub struct BinaryParameters<T = f64> {
+    pub total_mass: T,
+    pub mass_ratio: T,
+    pub chi_1: T,
+    pub chi_2: T,
+    pub ra: T,
+    pub dec: T,
+    pub psi: T,
+    pub theta_jn: T,
+    pub phi_c: T,
+    pub geocent_time: T,
+    pub luminosity_distance: T,
+}
+
+impl<T: Real> BinaryParameters<T> {
+    pub fn from_array(values: [T; 11]) -> Self {
+        let [total_mass, mass_ratio, chi_1, chi_2, ra, dec, psi, theta_jn, phi_c, geocent_time, luminosity_distance] =
+            values;
+        Self {
+            total_mass,
+            mass_ratio,
+            chi_1,
+            chi_2,
+            ra,
+            dec,
+            psi,
+            theta_jn,
+            phi_c,
+            geocent_time,
+            luminosity_distance,
+        }
+    }
+
+    pub fn to_array(&self) -> [T; 11] {
+        [
+            self.total_mass,
+            self.mass_ratio,
+            self.chi_1,
+            self.chi_2,
+            self.ra,
+            self.dec,
+            self.psi,
+            self.theta_jn,
+            self.phi_c,
+            self.geocent_time,
+            self.luminosity_distance,
+        ]
+    }
+}
+
+impl BinaryParameters {
+    pub fn extrinsic(&self) -> ExtrinsicParameters {
+        ExtrinsicParameters {
+            ra: self.ra,
+            dec: self.dec,
+            psi: self.psi,
+            theta_jn: self.theta_jn,
+            phi_c: self.phi_c,
+            geocent_time: self.geocent_time,
+            luminosity_distance: self.luminosity_distance,
+        }
+    }
+}
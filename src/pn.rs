@@ -1,32 +1,40 @@
-use std::f64::consts::PI;
-
-use crate::constants::EGAMMA;
+use crate::{
+    constants::EGAMMA,
+    numeric::{cast, Real},
+};
 
 // Euler Gamma is currently only in nightly builds of the standard library
 // see https://github.com/rust-lang/rust/issues/103883
+// The digits are parsed so types more precise than f64 keep them.
+const EGAMMA_DIGITS: &str = "0.577215664901532860606512090082402431";
+
+fn euler_gamma<T: Real>() -> T {
+    T::from_str_radix(EGAMMA_DIGITS, 10).unwrap_or_else(|_| cast(EGAMMA))
+}
 
-pub(crate) fn chi_pn(params: &Params) -> f64 {
-    let chi_s = (params.chi_1 + params.chi_2) / 2.0;
-    let chi_a = (params.chi_1 - params.chi_2) / 2.0;
+pub(crate) fn chi_pn<T: Real>(params: &Params<T>) -> T {
+    let c = cast::<T>;
+    let chi_s = (params.chi_1 + params.chi_2) / c(2.0);
+    let chi_a = (params.chi_1 - params.chi_2) / c(2.0);
 
-    return chi_s * (1.0 - params.eta * 76.0 / 113.0) + params.seta * chi_a;
+    chi_s * (T::one() - params.eta * (c(76.0) / c(113.0))) + params.seta * chi_a
 }
 
 #[derive(Clone, Copy, Debug)]
 #[allow(dead_code)]
-pub struct PNPhasing {
-    pub v: [f64; 16],
-    pub vlogv: [f64; 16],
-    pub vlogvlogv: [f64; 16],
+pub struct PNPhasing<T = f64> {
+    pub v: [T; 16],
+    pub vlogv: [T; 16],
+    pub vlogvlogv: [T; 16],
 }
 
-impl PNPhasing {
-    pub fn new(params: &Params) -> Self {
-        let scale = 3.0 / (128.0 * params.eta);
+impl<T: Real> PNPhasing<T> {
+    pub fn new(params: &Params<T>) -> Self {
+        let scale = cast::<T>(3.0) / cast::<T>(128.0) / params.eta;
         let mut new = Self {
-            v: [0.0; 16],
-            vlogv: [0.0; 16],
-            vlogvlogv: [0.0; 16],
+            v: [T::zero(); 16],
+            vlogv: [T::zero(); 16],
+            vlogvlogv: [T::zero(); 16],
         };
         new.v[0] = taylor_f2_phase_0(params) * scale;
         new.v[1] = taylor_f2_phase_1(params) * scale;
@@ -48,13 +56,13 @@ impl PNPhasing {
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct PNAmplitude {
-    pub v: [f64; 10],
+pub struct PNAmplitude<T = f64> {
+    pub v: [T; 10],
 }
 
-impl PNAmplitude {
-    pub fn new(params: &Params) -> Self {
-        let mut new = Self { v: [0.0; 10] };
+impl<T: Real> PNAmplitude<T> {
+    pub fn new(params: &Params<T>) -> Self {
+        let mut new = Self { v: [T::zero(); 10] };
         new.v[0] = taylor_f2_amplitude_0(params);
         new.v[1] = taylor_f2_amplitude_1(params);
         new.v[2] = taylor_f2_amplitude_2(params);
@@ -67,26 +75,26 @@ impl PNAmplitude {
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct Params {
-    pub eta: f64,
-    pub seta: f64,
-    pub chi_1: f64,
-    pub chi_2: f64,
-    pub mass_ratio: f64,
-    pub m1_on_m: f64,
-    pub m2_on_m: f64,
-    pub qm_def_1: f64,
-    pub qm_def_2: f64,
-    pub lambda_1: f64,
-    pub lambda_2: f64,
-}
-
-impl Params {
-    pub fn new(mass_ratio: f64, chi_1: f64, chi_2: f64) -> Self {
-        let eta = mass_ratio / (1.0 + mass_ratio).powi(2);
+pub struct Params<T = f64> {
+    pub eta: T,
+    pub seta: T,
+    pub chi_1: T,
+    pub chi_2: T,
+    pub mass_ratio: T,
+    pub m1_on_m: T,
+    pub m2_on_m: T,
+    pub qm_def_1: T,
+    pub qm_def_2: T,
+    pub lambda_1: T,
+    pub lambda_2: T,
+}
+
+impl<T: Real> Params<T> {
+    pub fn new(mass_ratio: T, chi_1: T, chi_2: T) -> Self {
+        let eta = mass_ratio / (T::one() + mass_ratio).powi(2);
         // seta = (m1 - m2) / (m1 + m2) = (1 - q) / (1 + q)
-        let m1_on_m = 1.0 / (1.0 + mass_ratio);
-        let m2_on_m = mass_ratio / (1.0 + mass_ratio);
+        let m1_on_m = T::one() / (T::one() + mass_ratio);
+        let m2_on_m = mass_ratio / (T::one() + mass_ratio);
         Self {
             eta,
             seta: m1_on_m - m2_on_m,
@@ -95,278 +103,368 @@ impl Params {
             mass_ratio,
             m1_on_m,
             m2_on_m,
-            qm_def_1: 1.0,
-            qm_def_2: 1.0,
-            lambda_1: 0.0,
-            lambda_2: 0.0,
+            qm_def_1: T::one(),
+            qm_def_2: T::one(),
+            lambda_1: T::zero(),
+            lambda_2: T::zero(),
         }
     }
 }
 
-pub fn _zero_function(_: &Params) -> f64 {
-    0.0
+pub fn _zero_function<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_amplitude_0(_: &Params) -> f64 {
-    1.0
+pub fn taylor_f2_amplitude_0<T: Real>(_: &Params<T>) -> T {
+    T::one()
 }
 
-pub fn taylor_f2_amplitude_1(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_amplitude_1<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_amplitude_2(args: &Params) -> f64 {
-    -323.0 / 224.0 + 451.0 * args.eta / 168.0
+pub fn taylor_f2_amplitude_2<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    (c(-323.0) / c(224.0)) + c(451.0) * args.eta / c(168.0)
 }
 
-pub fn taylor_f2_amplitude_3(args: &Params) -> f64 {
-    args.chi_1 * (27.0 * args.mass_ratio / 16.0 - 11.0 * args.eta / 12.0 + 27.0 / 16.0)
-        + args.chi_2 * (-27.0 * args.mass_ratio / 16.0 - 11.0 * args.eta / 12.0 + 27.0 / 16.0)
+pub fn taylor_f2_amplitude_3<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    args.chi_1
+        * (c(27.0) * args.mass_ratio / c(16.0) - c(11.0) * args.eta / c(12.0) + (c(27.0) / c(16.0)))
+        + args.chi_2
+            * (c(-27.0) * args.mass_ratio / c(16.0) - c(11.0) * args.eta / c(12.0)
+                + (c(27.0) / c(16.0)))
 }
 
-pub fn taylor_f2_amplitude_4(args: &Params) -> f64 {
-    args.chi_1.powi(2) * (-81.0 * args.mass_ratio / 64.0 + 81.0 * args.eta / 32.0 - 81.0 / 64.0)
+pub fn taylor_f2_amplitude_4<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    args.chi_1.powi(2)
+        * (c(-81.0) * args.mass_ratio / c(64.0) + c(81.0) * args.eta / c(32.0)
+            - (c(81.0) / c(64.0)))
         + args.chi_2.powi(2)
-            * (81.0 * args.mass_ratio / 64.0 + 81.0 * args.eta / 32.0 - 81.0 / 64.0)
-        + (105271.0 / 24192.0 * args.eta.powi(2)
-            - 1975055.0 / 338688.0 * args.eta
-            - 27312085.0 / 8128512.0)
-        - 47.0 / 16.0 * args.eta * args.chi_1 * args.chi_2
+            * (c(81.0) * args.mass_ratio / c(64.0) + c(81.0) * args.eta / c(32.0)
+                - (c(81.0) / c(64.0)))
+        + ((c(105271.0) / c(24192.0)) * args.eta.powi(2) - (c(1975055.0) / c(338688.0)) * args.eta
+            + (c(-27312085.0) / c(8128512.0)))
+        - (c(47.0) / c(16.0)) * args.eta * args.chi_1 * args.chi_2
 }
 
-pub fn taylor_f2_amplitude_5(args: &Params) -> f64 {
+pub fn taylor_f2_amplitude_5<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let pi = T::PI();
     args.chi_1.powi(3)
-        * (args.mass_ratio * (3.0 / 16.0 - 3.0 * args.eta / 16.0) - 9.0 * args.eta / 16.0
-            + 3.0 / 16.0)
+        * (args.mass_ratio * ((c(3.0) / c(16.0)) - c(3.0) * args.eta / c(16.0))
+            - c(9.0) * args.eta / c(16.0)
+            + (c(3.0) / c(16.0)))
         + args.chi_1
-            * (args.mass_ratio * (287213.0 / 32256.0 - 2083.0 * args.eta / 8064.0)
-                - 2227.0 * args.eta.powi(2) / 2016.0
-                - 15569.0 * args.eta / 1344.0
-                + 287213.0 / 32256.0)
+            * (args.mass_ratio * ((c(287213.0) / c(32256.0)) - c(2083.0) * args.eta / c(8064.0))
+                - c(2227.0) * args.eta.powi(2) / c(2016.0)
+                - c(15569.0) * args.eta / c(1344.0)
+                + (c(287213.0) / c(32256.0)))
         + args.chi_2.powi(3)
-            * (args.mass_ratio * (3.0 * args.eta / 16.0 - 3.0 / 16.0) - 9.0 * args.eta / 16.0
-                + 3.0 / 16.0)
+            * (args.mass_ratio * (c(3.0) * args.eta / c(16.0) - (c(3.0) / c(16.0)))
+                - c(9.0) * args.eta / c(16.0)
+                + (c(3.0) / c(16.0)))
         + args.chi_2
-            * (args.mass_ratio * (2083.0 * args.eta / 8064.0 - 287213.0 / 32256.0)
-                - 2227.0 * args.eta.powi(2) / 2016.0
-                - 15569.0 * args.eta / 1344.0
-                + 287213.0 / 32256.0)
-        - 85.0 * PI / 64.0
-        + 85.0 * PI * args.eta / 16.0
+            * (args.mass_ratio * (c(2083.0) * args.eta / c(8064.0) - (c(287213.0) / c(32256.0)))
+                - c(2227.0) * args.eta.powi(2) / c(2016.0)
+                - c(15569.0) * args.eta / c(1344.0)
+                + (c(287213.0) / c(32256.0)))
+        - c(85.0) * pi / c(64.0)
+        + c(85.0) * pi * args.eta / c(16.0)
 }
 
-pub fn taylor_f2_amplitude_6(args: &Params) -> f64 {
+pub fn taylor_f2_amplitude_6<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let pi = T::PI();
     args.chi_1
-        * (-17.0 * PI * args.mass_ratio / 12.0 + 5.0 * PI * args.eta / 3.0 - 17.0 * PI / 12.0)
+        * (c(-17.0) * pi * args.mass_ratio / c(12.0) + c(5.0) * pi * args.eta / c(3.0)
+            - c(17.0) * pi / c(12.0))
         + args.chi_2
-            * (17.0 * PI * args.mass_ratio / 12.0 + 5.0 * PI * args.eta / 3.0 - 17.0 * PI / 12.0)
+            * (c(17.0) * pi * args.mass_ratio / c(12.0) + c(5.0) * pi * args.eta / c(3.0)
+                - c(17.0) * pi / c(12.0))
         + args.chi_1
             * args.chi_2
-            * (-133249.0 * args.eta.powi(2) / 8064.0 - 319321.0 * args.eta / 32256.0)
+            * (c(-133249.0) * args.eta.powi(2) / c(8064.0) - c(319321.0) * args.eta / c(32256.0))
         + args.chi_1.powi(2)
-            * (args.mass_ratio * (-14139.0 * args.eta / 32256.0 - 49039.0 / 14336.0)
-                + 163199.0 * args.eta.powi(2) / 16128.0
-                + 158633.0 * args.eta / 64512.0
-                - 49039.0 / 14336.0)
+            * (args.mass_ratio * (c(-14139.0) * args.eta / c(32256.0) - (c(49039.0) / c(14336.0)))
+                + c(163199.0) * args.eta.powi(2) / c(16128.0)
+                + c(158633.0) * args.eta / c(64512.0)
+                - (c(49039.0) / c(14336.0)))
         + args.chi_2.powi(2)
-            * (args.mass_ratio * (14139.0 * args.eta / 32256.0 + 49039.0 / 14336.0)
-                + 163199.0 * args.eta.powi(2) / 16128.0
-                + 158633.0 * args.eta / 64512.0
-                - 49039.0 / 14336.0)
-        - 177520268561.0 / 8583708672.0
-        + (545384828789.0 / 5007163392.0 - 205.0 * PI.powi(2) / 48.0) * args.eta
-        - 3248849057.0 * args.eta.powi(2) / 178827264.0
-        + 34473079.0 * args.eta.powi(3) / 6386688.0
+            * (args.mass_ratio * (c(14139.0) * args.eta / c(32256.0) + (c(49039.0) / c(14336.0)))
+                + c(163199.0) * args.eta.powi(2) / c(16128.0)
+                + c(158633.0) * args.eta / c(64512.0)
+                - (c(49039.0) / c(14336.0)))
+        - (c(177520268561.0) / c(8583708672.0))
+        + ((c(545384828789.0) / c(5007163392.0)) - c(205.0) * pi.powi(2) / c(48.0)) * args.eta
+        - c(3248849057.0) * args.eta.powi(2) / c(178827264.0)
+        + c(34473079.0) * args.eta.powi(3) / c(6386688.0)
 }
 
-pub fn taylor_f2_phase_0(_: &Params) -> f64 {
-    1.0
+pub fn taylor_f2_phase_0<T: Real>(_: &Params<T>) -> T {
+    T::one()
 }
 
-pub fn taylor_f2_phase_1(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_1<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_2(args: &Params) -> f64 {
-    55.0 * args.eta / 9.0 + 3715.0 / 756.0
+pub fn taylor_f2_phase_2<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    c(55.0) * args.eta / c(9.0) + (c(3715.0) / c(756.0))
 }
 
-pub fn taylor_f2_phase_3(args: &Params) -> f64 {
-    let mut phase = -16.0 * PI;
-    for (m_on_m, chi) in vec![(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
-        phase += m_on_m * (25.0 + 38.0 / 3.0 * m_on_m) * chi;
+pub fn taylor_f2_phase_3<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = c(-16.0) * T::PI();
+    for (m_on_m, chi) in [(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
+        phase += m_on_m * (c(25.0) + (c(38.0) / c(3.0)) * m_on_m) * chi;
     }
     phase
 }
 
-pub fn taylor_f2_phase_4(args: &Params) -> f64 {
-    let mut phase =
-        15293365.0 / 508032.0 + 27145.0 / 504.0 * args.eta + 3085.0 / 72.0 * args.eta.powi(2);
-    phase -= 395.0 / 4.0 * args.eta * args.chi_1 * args.chi_2;
-    for (m_on_m, chi, qm_def) in vec![
+pub fn taylor_f2_phase_4<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = (c(15293365.0) / c(508032.0))
+        + (c(27145.0) / c(504.0)) * args.eta
+        + (c(3085.0) / c(72.0)) * args.eta.powi(2);
+    phase -= (c(395.0) / c(4.0)) * args.eta * args.chi_1 * args.chi_2;
+    for (m_on_m, chi, qm_def) in [
         (args.m1_on_m, args.chi_1, args.qm_def_1),
         (args.m2_on_m, args.chi_2, args.qm_def_2),
     ] {
-        phase -= (50.0 * qm_def + 5.0 / 8.0) * m_on_m.powi(2) * chi.powi(2);
+        phase -= (c(50.0) * qm_def + (c(5.0) / c(8.0))) * m_on_m.powi(2) * chi.powi(2);
     }
     phase
 }
 
-pub fn taylor_f2_phase_5(args: &Params) -> f64 {
-    let mut phase = 5.0 / 9.0 * (7729.0 / 84.0 - 13.0 * args.eta) * PI;
-    for (m_on_m, chi) in vec![(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
+pub fn taylor_f2_phase_5<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = (c(5.0) / c(9.0)) * ((c(7729.0) / c(84.0)) - c(13.0) * args.eta) * T::PI();
+    for (m_on_m, chi) in [(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
         phase -= chi
             * m_on_m
-            * (13915.0 / 84.0 - m_on_m * (1.0 - m_on_m) * 10.0 / 3.0
-                + m_on_m * (12760.0 / 81.0 + m_on_m * (1.0 - m_on_m) * 170.0 / 9.0));
+            * ((c(13915.0) / c(84.0)) - m_on_m * (T::one() - m_on_m) * c(10.0) / c(3.0)
+                + m_on_m
+                    * ((c(12760.0) / c(81.0)) + m_on_m * (T::one() - m_on_m) * c(170.0) / c(9.0)));
     }
     phase
 }
 
-pub fn taylor_f2_phase_6(args: &Params) -> f64 {
-    let mut phase =
-        11583231236531.0 / 4694215680.0 - 640.0 / 3.0 * PI.powi(2) - 6848.0 / 21.0 * EGAMMA;
-    phase += args.eta * (-15737765635.0 / 3048192.0 + 2255.0 / 12.0 * PI.powi(2));
-    phase += args.eta.powi(2) * 76055.0 / 1728.0 - args.eta.powi(3) * 127825.0 / 1296.0;
-    phase += taylor_f2_phase_6l(args) * 4.0f64.ln();
+pub fn taylor_f2_phase_6<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let pi = T::PI();
+    let mut phase = (c(11583231236531.0) / c(4694215680.0))
+        - (c(640.0) / c(3.0)) * pi.powi(2)
+        - (c(6848.0) / c(21.0)) * euler_gamma();
+    phase += args.eta * ((c(-15737765635.0) / c(3048192.0)) + (c(2255.0) / c(12.0)) * pi.powi(2));
+    phase += args.eta.powi(2) * c(76055.0) / c(1728.0) - args.eta.powi(3) * c(127825.0) / c(1296.0);
+    phase += taylor_f2_phase_6l(args) * c(4.0).ln();
     phase += taylor_3pn_ss(args);
-    for (m_on_m, chi) in vec![(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
-        phase += PI * m_on_m * (1490.0 / 3.0 + m_on_m * 260.0) * chi;
+    for (m_on_m, chi) in [(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
+        phase += pi * m_on_m * ((c(1490.0) / c(3.0)) + m_on_m * c(260.0)) * chi;
     }
     phase
 }
 
-pub fn taylor_3pn_ss(args: &Params) -> f64 {
-    let mut phase = (32675.0 / 112.0 + 5575.0 / 18.0 * args.eta) * args.chi_1 * args.chi_2;
-    for (m_on_m, chi, qm_def) in vec![
+pub fn taylor_3pn_ss<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase =
+        ((c(32675.0) / c(112.0)) + (c(5575.0) / c(18.0)) * args.eta) * args.chi_1 * args.chi_2;
+    for (m_on_m, chi, qm_def) in [
         (args.m1_on_m, args.chi_1, args.qm_def_1),
         (args.m2_on_m, args.chi_2, args.qm_def_2),
     ] {
-        phase += (47035.0 / 84.0 + 2935.0 / 6.0 * m_on_m - 120.0 * m_on_m.powi(2))
+        phase += ((c(47035.0) / c(84.0)) + (c(2935.0) / c(6.0)) * m_on_m
+            - c(120.0) * m_on_m.powi(2))
             * m_on_m.powi(2)
             * qm_def
             * chi.powi(2);
-        phase += (-410825.0 / 672.0 - 1085.0 / 12.0 * m_on_m + 1255.0 / 36.0 * m_on_m.powi(2))
+        phase += ((c(-410825.0) / c(672.0)) - (c(1085.0) / c(12.0)) * m_on_m
+            + (c(1255.0) / c(36.0)) * m_on_m.powi(2))
             * m_on_m.powi(2)
             * chi.powi(2);
     }
     phase
 }
 
-pub fn taylor_f2_phase_7(args: &Params) -> f64 {
-    let mut phase = PI
-        * (77096675.0 / 254016.0 + 378515.0 / 1512.0 * args.eta
-            - 74045.0 / 756.0 * args.eta.powi(2));
-    for (m_on_m, chi) in vec![(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
+pub fn taylor_f2_phase_7<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = T::PI()
+        * ((c(77096675.0) / c(254016.0)) + (c(378515.0) / c(1512.0)) * args.eta
+            - (c(74045.0) / c(756.0)) * args.eta.powi(2));
+    for (m_on_m, chi) in [(args.m1_on_m, args.chi_1), (args.m2_on_m, args.chi_2)] {
         phase += chi
             * m_on_m
-            * (-170978035.0 / 48384.0
-                + args.eta * 2876425.0 / 672.0
-                + args.eta.powi(2) * 4735.0 / 144.0
+            * ((c(-170978035.0) / c(48384.0))
+                + args.eta * c(2876425.0) / c(672.0)
+                + args.eta.powi(2) * c(4735.0) / c(144.0)
                 + m_on_m
-                    * (-7189233785.0 / 1524096.0 + args.eta * 458555.0 / 3024.0
-                        - args.eta.powi(2) * 5345.0 / 72.0));
+                    * ((c(-7189233785.0) / c(1524096.0)) + args.eta * c(458555.0) / c(3024.0)
+                        - args.eta.powi(2) * c(5345.0) / c(72.0)));
     }
     phase
 }
 
-pub fn taylor_f2_phase_8(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_8<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_9(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_9<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_10(args: &Params) -> f64 {
-    let mut phase = 0.0;
-    for (lambda, m_on_m) in vec![(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
-        phase += 24.0 * (-12.0 + 11.0 * m_on_m) * m_on_m.powi(4) * lambda;
+pub fn taylor_f2_phase_10<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = T::zero();
+    for (lambda, m_on_m) in [(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
+        phase += c(24.0) * (c(-12.0) + c(11.0) * m_on_m) * m_on_m.powi(4) * lambda;
     }
     phase
 }
 
-pub fn taylor_f2_phase_11(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_11<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_12(args: &Params) -> f64 {
-    let mut phase = 0.0;
-    for (lambda, m_on_m) in vec![(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
-        phase += (-15895.0 / 28.0 + 4595.0 / 28.0 * m_on_m + 5715.0 / 14.0 * m_on_m.powi(2)
-            - 325.0 / 7.0 * m_on_m.powi(3))
+pub fn taylor_f2_phase_12<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = T::zero();
+    for (lambda, m_on_m) in [(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
+        phase += ((c(-15895.0) / c(28.0))
+            + (c(4595.0) / c(28.0)) * m_on_m
+            + (c(5715.0) / c(14.0)) * m_on_m.powi(2)
+            - (c(325.0) / c(7.0)) * m_on_m.powi(3))
             * m_on_m.powi(4)
             * lambda;
     }
     phase
 }
 
-pub fn taylor_f2_phase_13(args: &Params) -> f64 {
-    let mut phase = 0.0;
-    for (lambda, m_on_m) in vec![(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
-        phase += 24.0 * (12.0 - 11.0 * m_on_m) * PI * m_on_m.powi(4) * lambda;
+pub fn taylor_f2_phase_13<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = T::zero();
+    for (lambda, m_on_m) in [(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
+        phase += c(24.0) * (c(12.0) - c(11.0) * m_on_m) * T::PI() * m_on_m.powi(4) * lambda;
     }
     phase
 }
 
-pub fn taylor_f2_phase_14(args: &Params) -> f64 {
-    let mut phase = 0.0;
-    for (lambda, m_on_m) in vec![(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
+pub fn taylor_f2_phase_14<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = T::zero();
+    for (lambda, m_on_m) in [(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
         phase += -(m_on_m.powi(4))
             * lambda
-            * 5.0
-            * (193986935.0 / 571536.0
-                - 14415613.0 / 381024.0 * m_on_m
-                - 57859.0 / 378.0 * m_on_m.powi(2)
-                - 209495.0 / 1512.0 * m_on_m.powi(3)
-                + 965.0 / 54.0 * m_on_m.powi(4)
-                - 4.0 * m_on_m.powi(5));
+            * c(5.0)
+            * ((c(193986935.0) / c(571536.0))
+                - (c(14415613.0) / c(381024.0)) * m_on_m
+                - (c(57859.0) / c(378.0)) * m_on_m.powi(2)
+                - (c(209495.0) / c(1512.0)) * m_on_m.powi(3)
+                + (c(965.0) / c(54.0)) * m_on_m.powi(4)
+                - c(4.0) * m_on_m.powi(5));
     }
     phase
 }
 
-pub fn taylor_f2_phase_15(args: &Params) -> f64 {
-    let mut phase = 0.0;
-    for (lambda, m_on_m) in vec![(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
-        phase += (m_on_m.powi(4)) * lambda * 1.0 / 28.0
-            * PI
-            * (27719.0 - 22415.0 * m_on_m + 7598.0 * m_on_m.powi(2) - 10520.0 * m_on_m.powi(3));
+pub fn taylor_f2_phase_15<T: Real>(args: &Params<T>) -> T {
+    let c = cast::<T>;
+    let mut phase = T::zero();
+    for (lambda, m_on_m) in [(args.lambda_1, args.m1_on_m), (args.lambda_2, args.m2_on_m)] {
+        phase += (m_on_m.powi(4)) * lambda / c(28.0)
+            * T::PI()
+            * (c(27719.0) - c(22415.0) * m_on_m + c(7598.0) * m_on_m.powi(2)
+                - c(10520.0) * m_on_m.powi(3));
     }
     phase
 }
 
-pub fn taylor_f2_phase_0l(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_0l<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_1l(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_1l<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_2l(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_2l<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_3l(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_3l<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_4l(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_4l<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_5l(args: &Params) -> f64 {
-    taylor_f2_phase_5(args) * 3.0
+pub fn taylor_f2_phase_5l<T: Real>(args: &Params<T>) -> T {
+    taylor_f2_phase_5(args) * cast(3.0)
 }
 
-pub fn taylor_f2_phase_6l(_: &Params) -> f64 {
-    -6848.0 / 21.0
+pub fn taylor_f2_phase_6l<T: Real>(_: &Params<T>) -> T {
+    cast::<T>(-6848.0) / cast::<T>(21.0)
 }
 
-pub fn taylor_f2_phase_7l(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_7l<T: Real>(_: &Params<T>) -> T {
+    T::zero()
 }
 
-pub fn taylor_f2_phase_8l(_: &Params) -> f64 {
-    0.0
+pub fn taylor_f2_phase_8l<T: Real>(_: &Params<T>) -> T {
+    T::zero()
+}
+
+/// Coefficients of v^n in the binding energy E = -eta v^2 / 2 (1 + ...) of a circular
+/// orbit, at 3PN for non-spinning binaries with the leading and next-to-leading spin-orbit
+/// terms, see Blanchet, Living Rev. Relativ. 17, 2 (2014).
+pub fn energy_coefficients<T: Real>(args: &Params<T>) -> [T; 7] {
+    let c = cast::<T>;
+    let eta = args.eta;
+    let spin = args.m1_on_m.powi(2) * args.chi_1 + args.m2_on_m.powi(2) * args.chi_2;
+    let sigma = args.m2_on_m * args.chi_2 - args.m1_on_m * args.chi_1;
+    let mut energy = [T::zero(); 7];
+    energy[0] = T::one();
+    energy[2] = -(c(3.0) / c(4.0)) - eta / c(12.0);
+    energy[3] = (c(14.0) / c(3.0)) * spin + c(2.0) * args.seta * sigma;
+    energy[4] = -(c(27.0) / c(8.0)) + (c(19.0) / c(8.0)) * eta - eta.powi(2) / c(24.0);
+    energy[5] = (c(11.0) - (c(61.0) / c(9.0)) * eta) * spin
+        + args.seta * (c(3.0) - (c(10.0) / c(3.0)) * eta) * sigma;
+    energy[6] = -(c(675.0) / c(64.0))
+        + ((c(34445.0) / c(576.0)) - (c(205.0) / c(96.0)) * T::PI().powi(2)) * eta
+        - (c(155.0) / c(96.0)) * eta.powi(2)
+        - (c(35.0) / c(5184.0)) * eta.powi(3);
+    energy
+}
+
+/// Orbital speed of the minimum energy circular orbit, where dE / dv = 0.
+///
+/// Found by bisection, if the energy has no minimum below v = 1 this returns 1.
+pub fn meco_orbital_speed<T: Real>(args: &Params<T>) -> T {
+    let energy = energy_coefficients(args);
+    // dE / dv up to a negative factor eta v
+    let slope = |v: T| {
+        let mut value = T::zero();
+        let mut cumulative_orbital_speed = T::one();
+        for (ii, &coefficient) in energy.iter().enumerate() {
+            value += cast::<T>(ii as f64 + 2.0) * coefficient * cumulative_orbital_speed;
+            cumulative_orbital_speed *= v;
+        }
+        value
+    };
+    let (mut lower, mut upper) = (cast::<T>(0.1), T::one());
+    if slope(upper) > T::zero() {
+        return upper;
+    }
+    for _ in 0..64 {
+        let middle = (lower + upper) / cast(2.0);
+        if slope(middle) > T::zero() {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+    }
+    (lower + upper) / cast(2.0)
 }
@@ -0,0 +1,571 @@
+use std::f64::consts::PI;
+
+use rand::{Rng, RngExt};
+
+use crate::{
+    conversion::{chirp_mass, component_masses_to_total_mass_jacobian, total_mass_from_chirp_mass},
+    cosmology::Cosmology,
+};
+
+// nodes of the tabulated mass ratio distribution, uniform in ln q
+const MASS_RATIO_NODES: usize = 2048;
+// nodes of the tabulated distance-redshift relation, uniform in redshift
+const REDSHIFT_NODES: usize = 1024;
+const BISECTION_STEPS: usize = 64;
+
+/// Distribution over `N` parameters defined by a map from the unit hypercube.
+pub trait Prior<const N: usize> {
+    /// Map a point in the unit hypercube to the parameters, the prior is the image of the
+    /// uniform distribution.
+    fn rescale(&self, unit: [f64; N]) -> [f64; N];
+    /// Normalised log density, -inf outside the support.
+    fn ln_prob(&self, values: &[f64; N]) -> f64;
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> [f64; N] {
+        self.rescale(std::array::from_fn(|_| rng.random()))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Uniform {
+    pub minimum: f64,
+    pub maximum: f64,
+}
+
+impl Uniform {
+    pub fn new(minimum: f64, maximum: f64) -> Self {
+        assert!(minimum < maximum, "empty prior range");
+        Self { minimum, maximum }
+    }
+}
+
+impl Prior<1> for Uniform {
+    fn rescale(&self, unit: [f64; 1]) -> [f64; 1] {
+        [self.minimum + unit[0] * (self.maximum - self.minimum)]
+    }
+
+    fn ln_prob(&self, values: &[f64; 1]) -> f64 {
+        if self.minimum <= values[0] && values[0] <= self.maximum {
+            -(self.maximum - self.minimum).ln()
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+}
+
+/// Uniform in the component masses with m1 >= m2, restricted to a range of chirp mass
+/// and a minimum mass ratio.
+///
+/// The parameters are the total mass and the mass ratio q = m2 / m1 as the model
+/// constructors take them. In chirp mass and mass ratio the density factorises into
+/// p(chirp mass) ∝ chirp mass and p(q) ∝ (1 + q)^(2/5) q^(-6/5), the latter is inverted
+/// from a table.
+#[derive(Clone, Debug)]
+pub struct ComponentMasses {
+    pub minimum_chirp_mass: f64,
+    pub maximum_chirp_mass: f64,
+    pub minimum_mass_ratio: f64,
+    cumulative: Vec<f64>,
+    ln_area: f64,
+}
+
+impl ComponentMasses {
+    pub fn new(minimum_chirp_mass: f64, maximum_chirp_mass: f64, minimum_mass_ratio: f64) -> Self {
+        assert!(
+            0.0 < minimum_chirp_mass && minimum_chirp_mass < maximum_chirp_mass,
+            "empty chirp mass range"
+        );
+        assert!(
+            0.0 < minimum_mass_ratio && minimum_mass_ratio < 1.0,
+            "minimum mass ratio must be between zero and one"
+        );
+        let mut new = Self {
+            minimum_chirp_mass,
+            maximum_chirp_mass,
+            minimum_mass_ratio,
+            cumulative: vec![0.0; MASS_RATIO_NODES],
+            ln_area: 0.0,
+        };
+        // trapezoid rule in ln q where the integrand is q p(q)
+        let step = new.ln_mass_ratio_step();
+        let integrand = |ii: usize| {
+            let mass_ratio = (minimum_mass_ratio.ln() + ii as f64 * step).exp();
+            (1.0 + mass_ratio).powf(0.4) * mass_ratio.powf(-0.2)
+        };
+        for ii in 1..MASS_RATIO_NODES {
+            new.cumulative[ii] =
+                new.cumulative[ii - 1] + (integrand(ii - 1) + integrand(ii)) * step / 2.0;
+        }
+        // the area of the region in the component mass plane
+        new.ln_area = ((maximum_chirp_mass.powi(2) - minimum_chirp_mass.powi(2)) / 2.0
+            * new.cumulative[MASS_RATIO_NODES - 1])
+            .ln();
+        new
+    }
+
+    fn ln_mass_ratio_step(&self) -> f64 {
+        -self.minimum_mass_ratio.ln() / (MASS_RATIO_NODES - 1) as f64
+    }
+}
+
+impl Prior<2> for ComponentMasses {
+    fn rescale(&self, unit: [f64; 2]) -> [f64; 2] {
+        let (lower, upper) = (
+            self.minimum_chirp_mass.powi(2),
+            self.maximum_chirp_mass.powi(2),
+        );
+        let chirp_mass = (lower + unit[0] * (upper - lower)).sqrt();
+
+        let target = unit[1] * self.cumulative[MASS_RATIO_NODES - 1];
+        let index = (self
+            .cumulative
+            .partition_point(|&value| value <= target)
+            .max(1)
+            - 1)
+        .min(MASS_RATIO_NODES - 2);
+        let fraction = (target - self.cumulative[index])
+            / (self.cumulative[index + 1] - self.cumulative[index]);
+        let mass_ratio = (self.minimum_mass_ratio.ln()
+            + (index as f64 + fraction) * self.ln_mass_ratio_step())
+        .exp()
+        .min(1.0);
+        [
+            total_mass_from_chirp_mass(chirp_mass, mass_ratio),
+            mass_ratio,
+        ]
+    }
+
+    fn ln_prob(&self, values: &[f64; 2]) -> f64 {
+        let [total_mass, mass_ratio] = *values;
+        if !(self.minimum_mass_ratio..=1.0).contains(&mass_ratio) {
+            return f64::NEG_INFINITY;
+        }
+        let chirp_mass = chirp_mass(total_mass, mass_ratio);
+        if !(self.minimum_chirp_mass..=self.maximum_chirp_mass).contains(&chirp_mass) {
+            return f64::NEG_INFINITY;
+        }
+        component_masses_to_total_mass_jacobian(total_mass, mass_ratio).ln() - self.ln_area
+    }
+}
+
+/// Component of a spin along the orbital angular momentum when the magnitude is uniform
+/// up to `maximum_magnitude` and the orientation is isotropic, p(chi) = -ln(|chi| / a) / 2a.
+#[derive(Clone, Copy, Debug)]
+pub struct AlignedSpin {
+    pub maximum_magnitude: f64,
+}
+
+impl AlignedSpin {
+    pub fn new(maximum_magnitude: f64) -> Self {
+        assert!(maximum_magnitude > 0.0, "maximum spin must be positive");
+        Self { maximum_magnitude }
+    }
+}
+
+impl Prior<1> for AlignedSpin {
+    fn rescale(&self, unit: [f64; 1]) -> [f64; 1] {
+        // the cumulative distribution is (1 + sign(chi) x (1 - ln x)) / 2 with x = |chi| / a
+        let target = (2.0 * unit[0] - 1.0).abs();
+        let (mut lower, mut upper) = (0.0, 1.0);
+        for _ in 0..BISECTION_STEPS {
+            let middle: f64 = (lower + upper) / 2.0;
+            if middle * (1.0 - middle.ln()) < target {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+        let magnitude = (lower + upper) / 2.0 * self.maximum_magnitude;
+        [magnitude.copysign(unit[0] - 0.5)]
+    }
+
+    fn ln_prob(&self, values: &[f64; 1]) -> f64 {
+        let scaled = values[0].abs() / self.maximum_magnitude;
+        if scaled > 1.0 {
+            return f64::NEG_INFINITY;
+        }
+        // the density diverges logarithmically at zero spin, clamping gives a finite value of
+        // about 354 / a at exactly zero, a set of measure zero so the normalisation is kept
+        (-scaled.max(f64::MIN_POSITIVE).ln() / (2.0 * self.maximum_magnitude)).ln()
+    }
+}
+
+/// Spin with magnitude uniform up to `maximum_magnitude` and isotropic orientation, the
+/// parameters are the magnitude, the tilt from the orbital angular momentum and the
+/// azimuth.
+#[derive(Clone, Copy, Debug)]
+pub struct IsotropicSpin {
+    pub maximum_magnitude: f64,
+}
+
+impl IsotropicSpin {
+    pub fn new(maximum_magnitude: f64) -> Self {
+        assert!(maximum_magnitude > 0.0, "maximum spin must be positive");
+        Self { maximum_magnitude }
+    }
+
+    /// The spin along the orbital angular momentum, distributed as `AlignedSpin`.
+    pub fn aligned_component(values: &[f64; 3]) -> f64 {
+        values[0] * values[1].cos()
+    }
+
+    /// Magnitude of the in-plane spin, as `conversion::chi_p` takes it.
+    pub fn in_plane_component(values: &[f64; 3]) -> f64 {
+        values[0] * values[1].sin()
+    }
+}
+
+impl Prior<3> for IsotropicSpin {
+    fn rescale(&self, unit: [f64; 3]) -> [f64; 3] {
+        [
+            unit[0] * self.maximum_magnitude,
+            (1.0 - 2.0 * unit[1]).acos(),
+            2.0 * PI * unit[2],
+        ]
+    }
+
+    fn ln_prob(&self, values: &[f64; 3]) -> f64 {
+        let [magnitude, tilt, azimuth] = *values;
+        if !(0.0..=self.maximum_magnitude).contains(&magnitude)
+            || !(0.0..=PI).contains(&tilt)
+            || !(0.0..=2.0 * PI).contains(&azimuth)
+        {
+            return f64::NEG_INFINITY;
+        }
+        (tilt.sin() / 2.0).ln() - self.maximum_magnitude.ln() - (2.0 * PI).ln()
+    }
+}
+
+/// Luminosity distance in Mpc distributed uniformly in comoving volume.
+///
+/// The redshift is interpolated from a table built on construction, so neither the
+/// transform nor the density integrate the cosmology.
+#[derive(Clone, Debug)]
+pub struct UniformComovingVolume {
+    pub cosmology: Cosmology,
+    pub minimum_distance: f64,
+    pub maximum_distance: f64,
+    minimum_volume: f64,
+    maximum_volume: f64,
+    redshifts: Vec<f64>,
+    luminosity_distances: Vec<f64>,
+    comoving_distances: Vec<f64>,
+}
+
+impl UniformComovingVolume {
+    pub fn new(cosmology: Cosmology, minimum_distance: f64, maximum_distance: f64) -> Self {
+        assert!(
+            0.0 <= minimum_distance && minimum_distance < maximum_distance,
+            "empty distance range"
+        );
+        let (minimum_redshift, maximum_redshift) = (
+            cosmology.redshift(minimum_distance),
+            cosmology.redshift(maximum_distance),
+        );
+        let redshifts: Vec<f64> = (0..REDSHIFT_NODES)
+            .map(|ii| {
+                minimum_redshift
+                    + (maximum_redshift - minimum_redshift) * ii as f64
+                        / (REDSHIFT_NODES - 1) as f64
+            })
+            .collect();
+        let comoving_distances: Vec<f64> = redshifts
+            .iter()
+            .map(|&redshift| cosmology.comoving_distance(redshift))
+            .collect();
+        let luminosity_distances = redshifts
+            .iter()
+            .zip(comoving_distances.iter())
+            .map(|(redshift, distance)| (1.0 + redshift) * distance)
+            .collect();
+        Self {
+            cosmology,
+            minimum_distance,
+            maximum_distance,
+            minimum_volume: cosmology.comoving_volume(minimum_redshift),
+            maximum_volume: cosmology.comoving_volume(maximum_redshift),
+            redshifts,
+            luminosity_distances,
+            comoving_distances,
+        }
+    }
+
+    // linear interpolation of the redshift against an increasing distance column, outside the
+    // table the end interval is extrapolated linearly. The columns span the distance range so
+    // that only happens by rounding at the ends, where the error is of the same order.
+    fn redshift_at(&self, distances: &[f64], distance: f64) -> f64 {
+        let upper = distances
+            .partition_point(|&value| value < distance)
+            .clamp(1, REDSHIFT_NODES - 1);
+        let lower = upper - 1;
+        let fraction = (distance - distances[lower]) / (distances[upper] - distances[lower]);
+        self.redshifts[lower] + fraction * (self.redshifts[upper] - self.redshifts[lower])
+    }
+}
+
+impl Prior<1> for UniformComovingVolume {
+    fn rescale(&self, unit: [f64; 1]) -> [f64; 1] {
+        let volume = self.minimum_volume + unit[0] * (self.maximum_volume - self.minimum_volume);
+        let comoving_distance = (3.0 * volume / (4.0 * PI)).cbrt();
+        let redshift = self.redshift_at(&self.comoving_distances, comoving_distance);
+        [(1.0 + redshift) * comoving_distance]
+    }
+
+    fn ln_prob(&self, values: &[f64; 1]) -> f64 {
+        let distance = values[0];
+        if !(self.minimum_distance..=self.maximum_distance).contains(&distance) {
+            return f64::NEG_INFINITY;
+        }
+        // d V / d d_L from the comoving distance d_L / (1 + z), without the integral
+        let redshift = self.redshift_at(&self.luminosity_distances, distance);
+        let comoving_distance = distance / (1.0 + redshift);
+        let hubble_distance = self.cosmology.hubble_distance() / self.cosmology.efunc(redshift);
+        let volume_derivative = 4.0 * PI * hubble_distance * comoving_distance.powi(2);
+        let distance_derivative = comoving_distance + (1.0 + redshift) * hubble_distance;
+        (volume_derivative / distance_derivative).ln()
+            - (self.maximum_volume - self.minimum_volume).ln()
+    }
+}
+
+/// Isotropic source location, the parameters are right ascension and declination.
+#[derive(Clone, Copy, Debug)]
+pub struct IsotropicSky;
+
+impl Prior<2> for IsotropicSky {
+    fn rescale(&self, unit: [f64; 2]) -> [f64; 2] {
+        [2.0 * PI * unit[0], (2.0 * unit[1] - 1.0).asin()]
+    }
+
+    fn ln_prob(&self, values: &[f64; 2]) -> f64 {
+        let [ra, dec] = *values;
+        if !(0.0..=2.0 * PI).contains(&ra) || !(-PI / 2.0..=PI / 2.0).contains(&dec) {
+            return f64::NEG_INFINITY;
+        }
+        (dec.cos() / (4.0 * PI)).ln()
+    }
+}
+
+/// Isotropic binary orientation, the parameters are the polarisation angle, the
+/// inclination `theta_jn` and the phase `phi_c` in the order of `BinaryParameters`.
+#[derive(Clone, Copy, Debug)]
+pub struct IsotropicOrientation;
+
+impl Prior<3> for IsotropicOrientation {
+    fn rescale(&self, unit: [f64; 3]) -> [f64; 3] {
+        [
+            PI * unit[0],
+            (1.0 - 2.0 * unit[1]).acos(),
+            2.0 * PI * unit[2],
+        ]
+    }
+
+    fn ln_prob(&self, values: &[f64; 3]) -> f64 {
+        let [psi, theta_jn, phi_c] = *values;
+        if !(0.0..=PI).contains(&psi)
+            || !(0.0..=PI).contains(&theta_jn)
+            || !(0.0..=2.0 * PI).contains(&phi_c)
+        {
+            return f64::NEG_INFINITY;
+        }
+        (theta_jn.sin() / 2.0).ln() - PI.ln() - (2.0 * PI).ln()
+    }
+}
+
+/// Prior on all of the `BINARY_PARAMETERS`, in their order.
+#[derive(Clone, Debug)]
+pub struct BinaryPrior {
+    pub masses: ComponentMasses,
+    pub chi_1: AlignedSpin,
+    pub chi_2: AlignedSpin,
+    pub sky: IsotropicSky,
+    pub orientation: IsotropicOrientation,
+    pub geocent_time: Uniform,
+    pub luminosity_distance: UniformComovingVolume,
+}
+
+impl Prior<11> for BinaryPrior {
+    fn rescale(&self, unit: [f64; 11]) -> [f64; 11] {
+        let [total_mass, mass_ratio] = self.masses.rescale([unit[0], unit[1]]);
+        let [chi_1] = self.chi_1.rescale([unit[2]]);
+        let [chi_2] = self.chi_2.rescale([unit[3]]);
+        let [ra, dec] = self.sky.rescale([unit[4], unit[5]]);
+        let [psi, theta_jn, phi_c] = self.orientation.rescale([unit[6], unit[7], unit[8]]);
+        let [geocent_time] = self.geocent_time.rescale([unit[9]]);
+        let [luminosity_distance] = self.luminosity_distance.rescale([unit[10]]);
+        [
+            total_mass,
+            mass_ratio,
+            chi_1,
+            chi_2,
+            ra,
+            dec,
+            psi,
+            theta_jn,
+            phi_c,
+            geocent_time,
+            luminosity_distance,
+        ]
+    }
+
+    fn ln_prob(&self, values: &[f64; 11]) -> f64 {
+        self.masses.ln_prob(&[values[0], values[1]])
+            + self.chi_1.ln_prob(&[values[2]])
+            + self.chi_2.ln_prob(&[values[3]])
+            + self.sky.ln_prob(&[values[4], values[5]])
+            + self.orientation.ln_prob(&[values[6], values[7], values[8]])
+            + self.geocent_time.ln_prob(&[values[9]])
+            + self.luminosity_distance.ln_prob(&[values[10]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // midpoint rule, which copes with the integrable singularities at the ends
+    fn integrate<F: Fn(f64) -> f64>(lower: f64, upper: f64, intervals: usize, function: F) -> f64 {
+        let step = (upper - lower) / intervals as f64;
+        (0..intervals)
+            .map(|ii| function(lower + (ii as f64 + 0.5) * step))
+            .sum::<f64>()
+            * step
+    }
+
+    // |det d rescale / d unit| by central differences and elimination with partial pivoting
+    fn rescale_jacobian<const N: usize, P: Prior<N>>(prior: &P, unit: [f64; N]) -> f64 {
+        let step = 1e-6;
+        let mut matrix = [[0.0; N]; N];
+        for jj in 0..N {
+            let (mut upper, mut lower) = (unit, unit);
+            upper[jj] += step;
+            lower[jj] -= step;
+            let (upper, lower) = (prior.rescale(upper), prior.rescale(lower));
+            for ii in 0..N {
+                matrix[ii][jj] = (upper[ii] - lower[ii]) / (2.0 * step);
+            }
+        }
+        let mut determinant = 1.0;
+        for column in 0..N {
+            let pivot = (column..N)
+                .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))
+                .unwrap();
+            matrix.swap(column, pivot);
+            determinant *= matrix[column][column];
+            let pivot_row = matrix[column];
+            for row in matrix.iter_mut().skip(column + 1) {
+                let factor = row[column] / pivot_row[column];
+                for (value, pivot) in row.iter_mut().zip(pivot_row).skip(column) {
+                    *value -= factor * pivot;
+                }
+            }
+        }
+        determinant.abs()
+    }
+
+    // the image of the uniform distribution has the prior density where the Jacobian of the
+    // map from the unit hypercube is the inverse of the density
+    fn assert_rescale_matches<const N: usize, P: Prior<N>>(prior: &P, tolerance: f64) {
+        for unit in [0.13, 0.37, 0.62, 0.88] {
+            let unit: [f64; N] = std::array::from_fn(|ii| (unit + 0.17 * ii as f64) % 1.0);
+            let density = prior.ln_prob(&prior.rescale(unit)).exp();
+            let product = density * rescale_jacobian(prior, unit);
+            assert!((product - 1.0).abs() < tolerance, "{unit:?}: {product}");
+        }
+    }
+
+    // the cumulative distribution at the image of each point matches the point
+    fn assert_cdf_matches<P: Prior<1>>(prior: &P, minimum: f64) {
+        for unit in [0.05, 0.3, 0.5, 0.75, 0.99] {
+            let [value] = prior.rescale([unit]);
+            let cdf = integrate(minimum, value, 100_000, |x| prior.ln_prob(&[x]).exp());
+            assert!((cdf - unit).abs() < 1e-4, "{unit}: {cdf}");
+        }
+    }
+
+    #[test]
+    fn uniform_is_normalised() {
+        let prior = Uniform::new(-2.0, 3.0);
+        let norm = integrate(-4.0, 5.0, 9000, |x| prior.ln_prob(&[x]).exp());
+        assert!((norm - 1.0).abs() < 1e-3);
+        assert_cdf_matches(&prior, -2.0);
+    }
+
+    #[test]
+    fn aligned_spin_is_normalised() {
+        let prior = AlignedSpin::new(0.8);
+        let norm = integrate(-0.8, 0.8, 100_000, |x| prior.ln_prob(&[x]).exp());
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_eq!(prior.ln_prob(&[0.9]), f64::NEG_INFINITY);
+        assert!(prior.ln_prob(&[0.0]).is_finite());
+        assert_cdf_matches(&prior, -0.8);
+        assert_rescale_matches(&prior, 1e-5);
+    }
+
+    #[test]
+    fn uniform_comoving_volume_is_normalised() {
+        let prior = UniformComovingVolume::new(Cosmology::planck18(), 10.0, 5000.0);
+        let norm = integrate(10.0, 5000.0, 100_000, |x| prior.ln_prob(&[x]).exp());
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_cdf_matches(&prior, 10.0);
+        assert_rescale_matches(&prior, 1e-4);
+        let [lower] = prior.rescale([0.0]);
+        let [upper] = prior.rescale([1.0]);
+        assert!((lower / 10.0 - 1.0).abs() < 1e-9 && (upper / 5000.0 - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn component_masses_are_normalised() {
+        let prior = ComponentMasses::new(10.0, 40.0, 0.1);
+        // in chirp mass and ln q, d total mass / d chirp mass = total mass / chirp mass
+        let norm = integrate(10.0, 40.0, 400, |chirp_mass| {
+            integrate(0.1_f64.ln(), 0.0, 2000, |ln_mass_ratio| {
+                let mass_ratio = ln_mass_ratio.exp();
+                let total_mass = total_mass_from_chirp_mass(chirp_mass, mass_ratio);
+                prior.ln_prob(&[total_mass, mass_ratio]).exp() * total_mass / chirp_mass
+                    * mass_ratio
+            })
+        });
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_rescale_matches(&prior, 2e-3);
+    }
+
+    #[test]
+    fn angular_priors_are_normalised() {
+        let norm = integrate(0.0, 2.0 * PI, 200, |ra| {
+            integrate(-PI / 2.0, PI / 2.0, 200, |dec| {
+                IsotropicSky.ln_prob(&[ra, dec]).exp()
+            })
+        });
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_rescale_matches(&IsotropicSky, 1e-5);
+
+        // uniform in the polarisation angle and phase, so only the inclination is integrated
+        let norm = integrate(0.0, PI, 200, |theta_jn| {
+            IsotropicOrientation.ln_prob(&[1.0, theta_jn, 2.0]).exp()
+        }) * PI
+            * 2.0
+            * PI;
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_rescale_matches(&IsotropicOrientation, 1e-5);
+
+        let prior = IsotropicSpin::new(0.9);
+        let norm =
+            integrate(0.0, PI, 200, |tilt| prior.ln_prob(&[0.5, tilt, 1.0]).exp()) * 0.9 * 2.0 * PI;
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_rescale_matches(&prior, 1e-5);
+    }
+
+    #[test]
+    fn binary_prior_matches_its_parts() {
+        let prior = BinaryPrior {
+            masses: ComponentMasses::new(10.0, 40.0, 0.1),
+            chi_1: AlignedSpin::new(0.8),
+            chi_2: AlignedSpin::new(0.8),
+            sky: IsotropicSky,
+            orientation: IsotropicOrientation,
+            geocent_time: Uniform::new(-0.1, 0.1),
+            luminosity_distance: UniformComovingVolume::new(Cosmology::planck18(), 10.0, 5000.0),
+        };
+        assert_rescale_matches(&prior, 2e-3);
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+const ALIGO_F0: f64 = 215.0;
+const ALIGO_S0: f64 = 1e-49;
+const ALIGO_F_LOWER: f64 = 10.0;
+const ET_F0: f64 = 200.0;
+const ET_S0: f64 = 1.449e-52;
+const ET_F_LOWER: f64 = 1.0;
+const VIRGO_F0: f64 = 300.0;
+const VIRGO_ASD0: f64 = 1.259e-24;
+const VIRGO_F_LOWER: f64 = 10.0;
+const KAGRA_F0: f64 = 100.0;
+const KAGRA_ASD0: f64 = 6.499e-25;
+const KAGRA_F_LOWER: f64 = 10.0;
+
+/// One-sided noise power spectral densities in 1 / Hz.
+///
+/// The analytic curves are infinite below their low-frequency cutoff, tabulated curves
+/// are infinite outside the tabulated range so those frequencies carry no weight.
+/// A+ (LIGO-T1800042) and Cosmic Explorer (LIGO-P1600143) have no published analytic fit,
+/// their released design curves are loaded with `from_asd_file`.
+#[derive(Clone, Debug)]
+pub enum PowerSpectralDensity {
+    /// Advanced LIGO zero-detuned high power fit, Sathyaprakash & Schutz (2009).
+    AdvancedLIGO,
+    /// Einstein Telescope ET-B fit, Mishra et al. (2010).
+    EinsteinTelescope,
+    /// Advanced Virgo design fit, Manzotti & Dietz (2012).
+    AdvancedVirgo,
+    /// KAGRA design fit, Manzotti & Dietz (2012).
+    KAGRA,
+    Tabulated {
+        frequencies: Vec<f64>,
+        values: Vec<f64>,
+    },
+}
+
+impl PowerSpectralDensity {
+    // Fails with `InvalidData` if the table cannot be interpolated in log-log.
+    pub fn tabulated(frequencies: Vec<f64>, values: Vec<f64>) -> io::Result<Self> {
+        check_table(&frequencies, &values)
+            .map_err(|message| io::Error::new(ErrorKind::InvalidData, message))?;
+        Ok(Self::Tabulated {
+            frequencies,
+            values,
+        })
+    }
+
+    // Two whitespace or comma separated columns, lines starting with # or % are skipped.
+    pub fn from_psd_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (frequencies, values) = read_columns(path.as_ref())?;
+        check_table(&frequencies, &values)
+            .map_err(|message| invalid_data(path.as_ref(), message))?;
+        Ok(Self::Tabulated {
+            frequencies,
+            values,
+        })
+    }
+
+    pub fn from_asd_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (frequencies, values) = read_columns(path.as_ref())?;
+        let values: Vec<f64> = values.iter().map(|x| x * x).collect();
+        check_table(&frequencies, &values)
+            .map_err(|message| invalid_data(path.as_ref(), message))?;
+        Ok(Self::Tabulated {
+            frequencies,
+            values,
+        })
+    }
+
+    pub fn psd(&self, frequency: f64) -> f64 {
+        match self {
+            Self::AdvancedLIGO => aligo(frequency, ALIGO_F_LOWER),
+            Self::AdvancedVirgo => advanced_virgo(frequency),
+            Self::KAGRA => kagra(frequency),
+            Self::EinsteinTelescope => einstein_telescope(frequency),
+            Self::Tabulated {
+                frequencies,
+                values,
+            } => log_interpolate(frequency, frequencies, values),
+        }
+    }
+
+    pub fn asd(&self, frequency: f64) -> f64 {
+        self.psd(frequency).sqrt()
+    }
+
+    pub fn evaluate(&self, frequencies: &[f64]) -> Vec<f64> {
+        frequencies.iter().map(|&f| self.psd(f)).collect()
+    }
+}
+
+fn aligo(frequency: f64, f_lower: f64) -> f64 {
+    if frequency < f_lower {
+        return f64::INFINITY;
+    }
+    let x = frequency / ALIGO_F0;
+    let x2 = x * x;
+    ALIGO_S0 * (x.powf(-4.14) - 5.0 / x2 + 111.0 * (1.0 - x2 + x2 * x2 / 2.0) / (1.0 + x2 / 2.0))
+}
+
+fn einstein_telescope(frequency: f64) -> f64 {
+    if frequency < ET_F_LOWER {
+        return f64::INFINITY;
+    }
+    let x = frequency / ET_F0;
+    let numerator = 1.0 + 31.18 * x - 64.72 * x.powi(2) + 52.24 * x.powi(3) - 42.16 * x.powi(4)
+        + 10.17 * x.powi(5)
+        + 11.53 * x.powi(6);
+    let denominator = 1.0 + 13.58 * x - 36.46 * x.powi(2) + 18.56 * x.powi(3) + 27.43 * x.powi(4);
+    ET_S0 * (x.powf(-4.05) + 185.62 * x.powf(-0.69) + 232.56 * numerator / denominator)
+}
+
+// The Manzotti & Dietz fits are sums of log-normal bumps in the amplitude spectral density,
+// as implemented in LALSimulation.
+fn advanced_virgo(frequency: f64) -> f64 {
+    if frequency < VIRGO_F_LOWER {
+        return f64::INFINITY;
+    }
+    let x = (frequency / VIRGO_F0).ln();
+    let x2 = x * x;
+    let asd = VIRGO_ASD0
+        * (0.07 * (-0.142 - 1.437 * x + 0.407 * x2).exp()
+            + 3.10 * (-0.466 - 1.043 * x - 0.548 * x2).exp()
+            + 0.40 * (-0.304 + 2.896 * x - 0.293 * x2).exp()
+            + 0.09 * (1.466 + 3.722 * x - 0.984 * x2).exp());
+    asd * asd
+}
+
+fn kagra(frequency: f64) -> f64 {
+    if frequency < KAGRA_F_LOWER {
+        return f64::INFINITY;
+    }
+    let x = (frequency / KAGRA_F0).ln();
+    let x2 = x * x;
+    let asd = KAGRA_ASD0
+        * (9.72e-9 * (-1.43 - 9.88 * x - 0.23 * x2).exp()
+            + 1.17 * (0.14 - 3.10 * x - 0.26 * x2).exp()
+            + 1.70 * (0.14 + 1.09 * x - 0.013 * x2).exp()
+            + 1.25 * (0.071 + 2.83 * x - 4.91 * x2).exp());
+    asd * asd
+}
+
+fn log_interpolate(frequency: f64, frequencies: &[f64], values: &[f64]) -> f64 {
+    if frequency < frequencies[0] || frequency > frequencies[frequencies.len() - 1] {
+        return f64::INFINITY;
+    }
+    let upper = frequencies
+        .partition_point(|&f| f < frequency)
+        .clamp(1, frequencies.len() - 1);
+    let lower = upper - 1;
+    let weight =
+        (frequency / frequencies[lower]).ln() / (frequencies[upper] / frequencies[lower]).ln();
+    values[lower] * (values[upper] / values[lower]).powf(weight)
+}
+
+fn check_table(frequencies: &[f64], values: &[f64]) -> Result<(), &'static str> {
+    if frequencies.len() != values.len() {
+        Err("frequencies and values must have the same length")
+    } else if frequencies.len() < 2 {
+        Err("need at least two samples")
+    } else if !frequencies.windows(2).all(|pair| pair[1] > pair[0]) {
+        Err("frequencies must be increasing")
+    } else if !frequencies.iter().chain(values.iter()).all(|&x| x > 0.0) {
+        Err("frequencies and values must be positive for log-log interpolation")
+    } else {
+        Ok(())
+    }
+}
+
+fn invalid_data(path: &Path, message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("{}: {message}", path.display()),
+    )
+}
+
+fn read_columns(path: &Path) -> io::Result<(Vec<f64>, Vec<f64>)> {
+    let contents = fs::read_to_string(path)?;
+    let mut frequencies = Vec::new();
+    let mut values = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+            continue;
+        }
+        let columns = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|column| !column.is_empty())
+            .map(|column| {
+                column
+                    .parse()
+                    .map_err(|_| invalid_data(path, format!("invalid number {column}")))
+            })
+            .collect::<io::Result<Vec<f64>>>()?;
+        if columns.len() < 2 {
+            return Err(invalid_data(
+                path,
+                format!("expected two columns, found {line}"),
+            ));
+        }
+        frequencies.push(columns[0]);
+        values.push(columns[1]);
+    }
+    Ok((frequencies, values))
+}
@@ -0,0 +1,608 @@
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, ErrorKind},
+    path::Path,
+    str::FromStr,
+};
+
+use num_complex::Complex;
+
+use crate::{
+    constants::MPC_SI,
+    inner_product::trapezoid_weights,
+    network::Network,
+    numeric::solve,
+    parameters::BinaryParameters,
+    psd::PowerSpectralDensity,
+    series::{FrequencySeries, Unit},
+    waveform::{Waveform, WaveformGenerator},
+};
+
+/// A reduced basis with its empirical interpolation nodes.
+#[derive(Clone, Debug)]
+pub struct ReducedBasis {
+    pub vectors: Vec<Vec<Complex<f64>>>,
+    pub nodes: Vec<usize>,
+}
+
+impl ReducedBasis {
+    /// Greedy orthonormal basis spanning the training vectors to within `tolerance`, the
+    /// largest squared projection error of a normalised training vector.
+    pub fn greedy(training: &[Vec<Complex<f64>>], tolerance: f64) -> Self {
+        // vectors negligible compared to the largest are rounding noise and are dropped
+        let norms: Vec<f64> = training
+            .iter()
+            .map(|vector| dot(vector, vector).re.sqrt())
+            .collect();
+        let threshold = 1e-10 * norms.iter().cloned().fold(0.0, f64::max);
+        let training: Vec<Vec<Complex<f64>>> = training
+            .iter()
+            .zip(norms.iter())
+            .filter(|(_, &norm)| norm > threshold)
+            .map(|(vector, norm)| vector.iter().map(|value| value / norm).collect())
+            .collect();
+        assert!(
+            !training.is_empty(),
+            "need at least one non-zero training vector"
+        );
+        let length = training[0].len();
+        let mut errors: Vec<f64> = vec![1.0; training.len()];
+        let mut vectors: Vec<Vec<Complex<f64>>> = Vec::new();
+        loop {
+            let (worst, &error) = errors
+                .iter()
+                .enumerate()
+                .max_by(|x, y| x.1.total_cmp(y.1))
+                .unwrap();
+            if error <= tolerance || vectors.len() == length {
+                break;
+            }
+            let mut vector = training[worst].clone();
+            // orthogonalise twice for numerical stability
+            for _ in 0..2 {
+                for basis in &vectors {
+                    let projection = dot(&vector, basis);
+                    for (value, &component) in vector.iter_mut().zip(basis.iter()) {
+                        *value -= projection * component;
+                    }
+                }
+            }
+            let norm = dot(&vector, &vector).re.sqrt();
+            if norm < 1e-12 {
+                break;
+            }
+            vector.iter_mut().for_each(|value| *value /= norm);
+            for (error, sample) in errors.iter_mut().zip(training.iter()) {
+                *error -= dot(sample, &vector).norm_sqr();
+            }
+            vectors.push(vector);
+        }
+        let nodes = interpolation_nodes(&vectors);
+        Self { vectors, nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    // Weights w such that sum_k a_k conj(h_k) ~ sum_i w_i conj(h(node_i)) for h in the span.
+    fn quadrature_weights(&self, a: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        let projections: Vec<Complex<f64>> = self
+            .vectors
+            .iter()
+            .map(|vector| a.iter().zip(vector.iter()).map(|(x, y)| x * y.conj()).sum())
+            .collect();
+        // solve V^H w = projections with V_ij = e_j(node_i)
+        let n = self.len();
+        let matrix: Vec<Vec<Complex<f64>>> = (0..n)
+            .map(|ii| {
+                (0..n)
+                    .map(|jj| self.vectors[ii][self.nodes[jj]].conj())
+                    .collect()
+            })
+            .collect();
+        solve(matrix, projections, |value: Complex<f64>| value.norm())
+    }
+}
+
+// Empirical interpolation nodes, each is where the next basis vector is worst interpolated
+// by the previous ones.
+fn interpolation_nodes(vectors: &[Vec<Complex<f64>>]) -> Vec<usize> {
+    let argmax = |values: &[Complex<f64>]| {
+        values
+            .iter()
+            .enumerate()
+            .max_by(|x, y| x.1.norm().total_cmp(&y.1.norm()))
+            .unwrap()
+            .0
+    };
+    let mut nodes: Vec<usize> = Vec::new();
+    for (jj, vector) in vectors.iter().enumerate() {
+        if jj == 0 {
+            nodes.push(argmax(vector));
+            continue;
+        }
+        let matrix: Vec<Vec<Complex<f64>>> = (0..jj)
+            .map(|aa| (0..jj).map(|bb| vectors[bb][nodes[aa]]).collect())
+            .collect();
+        let values: Vec<Complex<f64>> = nodes.iter().map(|&node| vector[node]).collect();
+        let coefficients = solve(matrix, values, |value: Complex<f64>| value.norm());
+        let mut residual = vector.clone();
+        for (coefficient, basis) in coefficients.iter().zip(vectors.iter()) {
+            for (value, &component) in residual.iter_mut().zip(basis.iter()) {
+                *value -= coefficient * component;
+            }
+        }
+        nodes.push(argmax(&residual));
+    }
+    nodes
+}
+
+fn dot(a: &[Complex<f64>], b: &[Complex<f64>]) -> Complex<f64> {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y.conj()).sum()
+}
+
+/// Linear and quadratic reduced bases on a frequency grid.
+///
+/// The linear basis spans the plus and cross polarisations, the quadratic basis their
+/// squared magnitudes and cross term, so any sky position and polarisation is covered.
+/// The training samples must span the expected `geocent_time - epoch` including the
+/// light travel time to the detectors, so the basis only applies to data with the same
+/// `epoch`.
+#[derive(Clone, Debug)]
+pub struct RoqBasis {
+    pub epoch: f64,
+    pub frequencies: Vec<f64>,
+    pub linear: ReducedBasis,
+    pub quadratic: ReducedBasis,
+}
+
+impl RoqBasis {
+    pub fn build<F: WaveformGenerator>(
+        model: F,
+        samples: &[BinaryParameters],
+        frequencies: &[f64],
+        epoch: f64,
+        tolerance: f64,
+    ) -> Self {
+        let mut linear = Vec::new();
+        let mut quadratic = Vec::new();
+        for sample in samples {
+            let (plus, cross) = model.generate(sample).waveform_modes(
+                frequencies,
+                sample.phi_c,
+                sample.geocent_time - epoch,
+                sample.theta_jn,
+            );
+            quadratic.push(plus.data.iter().map(|x| x.norm_sqr().into()).collect());
+            quadratic.push(cross.data.iter().map(|x| x.norm_sqr().into()).collect());
+            quadratic.push(
+                plus.data
+                    .iter()
+                    .zip(cross.data.iter())
+                    .map(|(x, y)| (x * y.conj()).re.into())
+                    .collect(),
+            );
+            linear.push(plus.data);
+            linear.push(cross.data);
+        }
+        Self {
+            epoch,
+            frequencies: frequencies.to_vec(),
+            linear: ReducedBasis::greedy(&linear, tolerance),
+            quadratic: ReducedBasis::greedy(&quadratic, tolerance),
+        }
+    }
+
+    pub fn linear_nodes(&self) -> Vec<f64> {
+        self.linear
+            .nodes
+            .iter()
+            .map(|&ii| self.frequencies[ii])
+            .collect()
+    }
+
+    pub fn quadratic_nodes(&self) -> Vec<f64> {
+        self.quadratic
+            .nodes
+            .iter()
+            .map(|&ii| self.frequencies[ii])
+            .collect()
+    }
+
+    // Plain text: the epoch, the frequencies, then for each basis its size, a line of node
+    // indices and one line per basis vector of interleaved real and imaginary parts.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let join = |values: Vec<String>| values.join(" ");
+        let mut lines = vec![
+            self.epoch.to_string(),
+            join(self.frequencies.iter().map(|f| f.to_string()).collect()),
+        ];
+        for basis in [&self.linear, &self.quadratic] {
+            lines.push(basis.len().to_string());
+            lines.push(join(
+                basis.nodes.iter().map(|node| node.to_string()).collect(),
+            ));
+            for vector in &basis.vectors {
+                lines.push(join(
+                    vector
+                        .iter()
+                        .map(|value| format!("{:e} {:e}", value.re, value.im))
+                        .collect(),
+                ));
+            }
+        }
+        fs::write(path, lines.join("\n") + "\n")
+    }
+
+    /// Read a basis written by `save`, the shapes of both bases are checked against the
+    /// frequencies.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let invalid = |message: String| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: {message}", path.display()),
+            )
+        };
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines();
+        let mut line = |name: &str| {
+            lines
+                .next()
+                .ok_or_else(|| invalid(format!("unexpected end of file reading {name}")))
+        };
+        let epoch = match parse_numbers::<f64>(line("epoch")?).map_err(invalid)?[..] {
+            [epoch] => epoch,
+            _ => return Err(invalid("expected the epoch".to_string())),
+        };
+        let frequencies: Vec<f64> = parse_numbers(line("frequencies")?).map_err(invalid)?;
+        if frequencies.is_empty() {
+            return Err(invalid("no frequencies".to_string()));
+        }
+        let mut bases = Vec::new();
+        for name in ["linear basis", "quadratic basis"] {
+            let size = match parse_numbers::<usize>(line(name)?).map_err(invalid)?[..] {
+                [size] => size,
+                _ => return Err(invalid(format!("expected the size of the {name}"))),
+            };
+            let nodes: Vec<usize> = parse_numbers(line(name)?).map_err(invalid)?;
+            if nodes.len() != size || nodes.iter().any(|&node| node >= frequencies.len()) {
+                return Err(invalid(format!(
+                    "expected {size} nodes below {} for the {name}",
+                    frequencies.len()
+                )));
+            }
+            let mut vectors = Vec::with_capacity(size);
+            for _ in 0..size {
+                let values: Vec<f64> = parse_numbers(line(name)?).map_err(invalid)?;
+                if values.len() != 2 * frequencies.len() {
+                    return Err(invalid(format!(
+                        "expected {} values per vector of the {name}, found {}",
+                        2 * frequencies.len(),
+                        values.len()
+                    )));
+                }
+                vectors.push(
+                    values
+                        .chunks(2)
+                        .map(|pair| Complex::new(pair[0], pair[1]))
+                        .collect(),
+                );
+            }
+            bases.push(ReducedBasis { vectors, nodes });
+        }
+        if lines.any(|line| !line.trim().is_empty()) {
+            return Err(invalid(
+                "unexpected data after the quadratic basis".to_string(),
+            ));
+        }
+        let quadratic = bases.pop().unwrap();
+        let linear = bases.pop().unwrap();
+        Ok(Self {
+            epoch,
+            frequencies,
+            linear,
+            quadratic,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RoqError {
+    /// The data of `detector` is not defined on the frequencies of the basis.
+    DifferentFrequencies { detector: usize },
+    /// The data of `detector` has a different epoch to the training samples of the basis.
+    DifferentEpoch {
+        detector: usize,
+        data: f64,
+        basis: f64,
+    },
+}
+
+impl fmt::Display for RoqError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DifferentFrequencies { detector } => write!(
+                f,
+                "data of detector {detector} and the basis have different frequencies"
+            ),
+            Self::DifferentEpoch {
+                detector,
+                data,
+                basis,
+            } => write!(
+                f,
+                "data of detector {detector} has epoch {data}, the basis was built for {basis}"
+            ),
+        }
+    }
+}
+
+impl Error for RoqError {}
+
+/// Gaussian likelihood by reduced-order quadrature, the model is only evaluated at the
+/// interpolation nodes of the basis.
+pub struct RoqLikelihood<F> {
+    pub network: Network,
+    pub model: F,
+    linear_nodes: Vec<f64>,
+    quadratic_nodes: Vec<f64>,
+    epochs: Vec<f64>,
+    linear_weights: Vec<Vec<Complex<f64>>>,
+    quadratic_weights: Vec<Vec<f64>>,
+    d_inner_d: f64,
+}
+
+impl<F: WaveformGenerator> RoqLikelihood<F> {
+    pub fn new(
+        network: Network,
+        data: &[FrequencySeries],
+        psds: &[PowerSpectralDensity],
+        model: F,
+        basis: &RoqBasis,
+    ) -> Result<Self, RoqError> {
+        assert_eq!(network.len(), data.len(), "need data for each detector");
+        assert_eq!(network.len(), psds.len(), "need one psd per detector");
+        let grid = FrequencySeries::zeros(basis.frequencies.clone(), basis.epoch, Unit::Strain);
+        for (detector, strain) in data.iter().enumerate() {
+            if !grid.same_frequencies(strain) {
+                return Err(RoqError::DifferentFrequencies { detector });
+            }
+            // the epoch is the phase reference of the training waveforms
+            if (strain.epoch - basis.epoch).abs() > 4.0 * f64::EPSILON * basis.epoch.abs() {
+                return Err(RoqError::DifferentEpoch {
+                    detector,
+                    data: strain.epoch,
+                    basis: basis.epoch,
+                });
+            }
+        }
+        let integration = trapezoid_weights(&basis.frequencies);
+        let mut d_inner_d = 0.0;
+        let mut linear_weights = Vec::new();
+        let mut quadratic_weights = Vec::new();
+        for (strain, psd) in data.iter().zip(psds.iter()) {
+            let weights: Vec<f64> = integration
+                .iter()
+                .zip(psd.evaluate(&basis.frequencies))
+                .map(|(weight, power)| 4.0 * weight / power)
+                .collect();
+            d_inner_d += strain
+                .data
+                .iter()
+                .zip(weights.iter())
+                .map(|(value, weight)| value.norm_sqr() * weight)
+                .sum::<f64>();
+            let weighted: Vec<Complex<f64>> = strain
+                .data
+                .iter()
+                .zip(weights.iter())
+                .map(|(value, weight)| value * weight)
+                .collect();
+            linear_weights.push(basis.linear.quadrature_weights(&weighted));
+            let weights: Vec<Complex<f64>> = weights.iter().map(|&x| x.into()).collect();
+            quadratic_weights.push(
+                basis
+                    .quadratic
+                    .quadrature_weights(&weights)
+                    .iter()
+                    .map(|value| value.re)
+                    .collect(),
+            );
+        }
+        Ok(Self {
+            network,
+            model,
+            linear_nodes: basis.linear_nodes(),
+            quadratic_nodes: basis.quadratic_nodes(),
+            epochs: data.iter().map(|strain| strain.epoch).collect(),
+            linear_weights,
+            quadratic_weights,
+            d_inner_d,
+        })
+    }
+
+    fn strain(&self, parameters: &BinaryParameters, frequencies: &[f64]) -> Vec<FrequencySeries> {
+        let model = self.model.generate(parameters);
+        let scale = model.luminosity_distance() / (parameters.luminosity_distance * MPC_SI);
+        let (plus, cross) =
+            model.waveform_modes(frequencies, parameters.phi_c, 0.0, parameters.theta_jn);
+        let (plus, cross) = (&plus * scale, &cross * scale);
+        let extrinsic = parameters.extrinsic();
+        self.network
+            .detectors
+            .iter()
+            .zip(self.epochs.iter())
+            .map(|(detector, &epoch)| detector.project(&plus, &cross, &extrinsic, epoch))
+            .collect()
+    }
+
+    /// <d|h> and <h|h> summed over the network.
+    pub fn inner_products(&self, parameters: &BinaryParameters) -> (f64, f64) {
+        let linear = self.strain(parameters, &self.linear_nodes);
+        let quadratic = self.strain(parameters, &self.quadratic_nodes);
+        let mut d_inner_h = 0.0;
+        let mut h_inner_h = 0.0;
+        for ii in 0..self.network.len() {
+            d_inner_h += linear[ii]
+                .data
+                .iter()
+                .zip(self.linear_weights[ii].iter())
+                .map(|(value, weight)| (weight * value.conj()).re)
+                .sum::<f64>();
+            h_inner_h += quadratic[ii]
+                .data
+                .iter()
+                .zip(self.quadratic_weights[ii].iter())
+                .map(|(value, weight)| weight * value.norm_sqr())
+                .sum::<f64>();
+        }
+        (d_inner_h, h_inner_h)
+    }
+
+    pub fn noise_log_likelihood(&self) -> f64 {
+        -self.d_inner_d / 2.0
+    }
+
+    pub fn log_likelihood_ratio(&self, parameters: &BinaryParameters) -> f64 {
+        let (d_inner_h, h_inner_h) = self.inner_products(parameters);
+        d_inner_h - h_inner_h / 2.0
+    }
+
+    pub fn log_likelihood(&self, parameters: &BinaryParameters) -> f64 {
+        self.log_likelihood_ratio(parameters) + self.noise_log_likelihood()
+    }
+}
+
+fn parse_numbers<T: FromStr>(line: &str) -> Result<Vec<T>, String> {
+    line.split_whitespace()
+        .map(|value| value.parse().map_err(|_| format!("invalid number {value}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{rngs::StdRng, RngExt, SeedableRng};
+
+    use super::*;
+    use crate::{likelihood::GaussianLikelihood, taylor::TaylorF2Generator};
+
+    const EPOCH: f64 = 1126259460.4;
+
+    fn truth() -> BinaryParameters {
+        BinaryParameters {
+            total_mass: 30.0,
+            mass_ratio: 0.7,
+            chi_1: 0.2,
+            chi_2: 0.0,
+            ra: 1.0,
+            dec: -0.5,
+            psi: 0.3,
+            theta_jn: 0.4,
+            phi_c: 1.1,
+            geocent_time: EPOCH + 2.0,
+            luminosity_distance: 400.0,
+        }
+    }
+
+    fn frequencies() -> Vec<f64> {
+        (0..481).map(|ii| 20.0 + 0.25 * ii as f64).collect()
+    }
+
+    // Training samples about the truth covering the light travel time to the detectors.
+    fn basis() -> RoqBasis {
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples: Vec<BinaryParameters> = (0..200)
+            .map(|_| {
+                let mut sample = truth();
+                sample.total_mass += rng.random_range(-0.2..0.2);
+                sample.mass_ratio += rng.random_range(-0.05..0.05);
+                sample.chi_1 += rng.random_range(-0.05..0.05);
+                sample.theta_jn = rng.random_range(0.0..PI);
+                sample.phi_c = rng.random_range(0.0..2.0 * PI);
+                sample.geocent_time += rng.random_range(-0.05..0.05);
+                sample
+            })
+            .collect();
+        let model = TaylorF2Generator::new(20.0);
+        RoqBasis::build(model, &samples, &frequencies(), EPOCH, 1e-10)
+    }
+
+    fn injection(epoch: f64) -> (Network, Vec<FrequencySeries>) {
+        let network = Network::from_names(&["H1", "L1"]).unwrap();
+        let truth = truth();
+        let model = TaylorF2Generator::new(20.0).generate(&truth);
+        let data = network.strain(&model, &frequencies(), &truth.extrinsic(), epoch);
+        (network, data)
+    }
+
+    fn psds() -> [PowerSpectralDensity; 2] {
+        [
+            PowerSpectralDensity::AdvancedLIGO,
+            PowerSpectralDensity::AdvancedLIGO,
+        ]
+    }
+
+    #[test]
+    fn roq_matches_the_exact_likelihood() {
+        let basis = basis();
+        assert!(basis.linear.len() < frequencies().len() / 2);
+        let (network, data) = injection(EPOCH);
+        let model = TaylorF2Generator::new(20.0);
+        let roq = RoqLikelihood::new(network.clone(), &data, &psds(), model, &basis).unwrap();
+        let exact = GaussianLikelihood::new(network, data, &psds(), model);
+        for (index, offset) in [(0, 0.0), (0, 0.1), (1, -0.02), (7, 0.5), (9, 0.01)] {
+            let mut values = truth().to_array();
+            values[index] += offset;
+            let parameters = BinaryParameters::from_array(values);
+            let expected = exact.log_likelihood_ratio(&parameters);
+            let value = roq.log_likelihood_ratio(&parameters);
+            assert!(
+                (value - expected).abs() < 1e-2,
+                "parameter {index}: {value} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn roq_rejects_data_the_basis_was_not_built_for() {
+        let basis = basis();
+        let model = TaylorF2Generator::new(20.0);
+        let (network, data) = injection(EPOCH + 1.0);
+        assert_eq!(
+            RoqLikelihood::new(network, &data, &psds(), model, &basis).err(),
+            Some(RoqError::DifferentEpoch {
+                detector: 0,
+                data: EPOCH + 1.0,
+                basis: EPOCH
+            })
+        );
+        let (network, mut data) = injection(EPOCH);
+        data[1] = data[1].slice(1..data[1].len());
+        assert_eq!(
+            RoqLikelihood::new(network, &data, &psds(), model, &basis).err(),
+            Some(RoqError::DifferentFrequencies { detector: 1 })
+        );
+    }
+
+    #[test]
+    fn saved_basis_loads_unchanged() {
+        let basis = basis();
+        let path = std::env::temp_dir().join(format!("roq-basis-{}.txt", std::process::id()));
+        basis.save(&path).unwrap();
+        let loaded = RoqBasis::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.epoch, basis.epoch);
+        assert_eq!(loaded.frequencies, basis.frequencies);
+        assert_eq!(loaded.linear_nodes(), basis.linear_nodes());
+        assert_eq!(loaded.quadratic.vectors, basis.quadratic.vectors);
+    }
+}
@@ -0,0 +1,449 @@
+use std::f64::consts::PI;
+use std::ops::{Add, AddAssign, Mul, Neg, Range, Sub, SubAssign};
+
+use num_complex::Complex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Dimensionless,
+    Strain,
+    StrainPerHertz,
+    PerHertz,
+}
+
+impl Unit {
+    // `Unit` has no compound units so only products with a dimensionless factor have one
+    pub fn product(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Unit::Dimensionless, unit) | (unit, Unit::Dimensionless) => Some(unit),
+            _ => None,
+        }
+    }
+}
+
+// Frequency series carry the time of the origin of their Fourier phases as the epoch,
+// a signal at time t contributes exp(-2 pi i f (t - epoch)). Waveform models use epoch = 0.
+#[derive(Clone, Debug)]
+pub struct FrequencySeries {
+    pub data: Vec<Complex<f64>>,
+    pub epoch: f64,
+    pub units: Unit,
+    frequencies: Vec<f64>,
+    delta_f: Option<f64>,
+}
+
+impl FrequencySeries {
+    pub fn new(data: Vec<Complex<f64>>, frequencies: Vec<f64>, epoch: f64, units: Unit) -> Self {
+        assert_eq!(
+            data.len(),
+            frequencies.len(),
+            "data and frequencies must have the same length"
+        );
+        let delta_f = uniform_spacing(&frequencies);
+        Self {
+            data,
+            epoch,
+            units,
+            frequencies,
+            delta_f,
+        }
+    }
+
+    pub fn uniform(
+        data: Vec<Complex<f64>>,
+        f_lower: f64,
+        delta_f: f64,
+        epoch: f64,
+        units: Unit,
+    ) -> Self {
+        let frequencies = (0..data.len())
+            .map(|ii| f_lower + ii as f64 * delta_f)
+            .collect();
+        Self {
+            data,
+            epoch,
+            units,
+            frequencies,
+            delta_f: Some(delta_f),
+        }
+    }
+
+    pub fn zeros(frequencies: Vec<f64>, epoch: f64, units: Unit) -> Self {
+        let data = vec![Complex::new(0.0, 0.0); frequencies.len()];
+        Self::new(data, frequencies, epoch, units)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    // None if the frequencies are not uniformly spaced
+    pub fn delta_f(&self) -> Option<f64> {
+        self.delta_f
+    }
+
+    // None for an empty series
+    pub fn f_lower(&self) -> Option<f64> {
+        self.frequencies.first().copied()
+    }
+
+    pub fn f_upper(&self) -> Option<f64> {
+        self.frequencies.last().copied()
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            data: self.data[range.clone()].to_vec(),
+            epoch: self.epoch,
+            units: self.units,
+            frequencies: self.frequencies[range.clone()].to_vec(),
+            delta_f: if range.len() > 1 { self.delta_f } else { None },
+        }
+    }
+
+    // The samples with f_min <= f <= f_max, assuming increasing frequencies.
+    pub fn select_band(&self, f_min: f64, f_max: f64) -> Self {
+        self.slice(self.band_indices(f_min, f_max))
+    }
+
+    pub fn band_indices(&self, f_min: f64, f_max: f64) -> Range<usize> {
+        let start = self.frequencies.partition_point(|&f| f < f_min);
+        let end = self.frequencies.partition_point(|&f| f <= f_max);
+        start..end.max(start)
+    }
+
+    pub fn conj(&self) -> Self {
+        self.map(|value| value.conj())
+    }
+
+    pub fn map<F: Fn(Complex<f64>) -> Complex<f64>>(&self, function: F) -> Self {
+        Self {
+            data: self.data.iter().map(|&value| function(value)).collect(),
+            epoch: self.epoch,
+            units: self.units,
+            frequencies: self.frequencies.clone(),
+            delta_f: self.delta_f,
+        }
+    }
+
+    // Re-reference the Fourier phases to a new epoch without moving the signal.
+    pub fn with_epoch(&self, epoch: f64) -> Self {
+        let shift = epoch - self.epoch;
+        let mut new = self.clone();
+        for (value, &frequency) in new.data.iter_mut().zip(self.frequencies.iter()) {
+            *value *= Complex::new(0.0, 2.0 * PI * frequency * shift).exp();
+        }
+        new.epoch = epoch;
+        new
+    }
+
+    // Same length and frequencies to rounding, uniform grids compare their start and spacing.
+    pub(crate) fn same_frequencies(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        match (self.f_lower(), self.delta_f, other.delta_f) {
+            (Some(f_lower), Some(delta_f), Some(other_delta_f)) => {
+                (f_lower - other.frequencies[0]).abs() <= 1e-10 * f_lower.abs().max(1.0)
+                    && (delta_f - other_delta_f).abs() <= 1e-10 * delta_f.abs()
+            }
+            _ => self
+                .frequencies
+                .iter()
+                .zip(other.frequencies.iter())
+                .all(|(a, b)| (a - b).abs() <= 1e-10 * a.abs().max(1.0)),
+        }
+    }
+
+    // The epoch is the phase reference so it must agree to rounding, re-reference one
+    // series with `with_epoch` first otherwise.
+    pub(crate) fn check_compatible(&self, other: &Self) {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "frequency series have different lengths"
+        );
+        assert!(
+            self.same_frequencies(other),
+            "frequency series are defined on different frequencies"
+        );
+        assert!(
+            (self.epoch - other.epoch).abs() <= 4.0 * f64::EPSILON * self.epoch.abs(),
+            "frequency series have different epochs {} and {}",
+            self.epoch,
+            other.epoch
+        );
+    }
+
+    fn check_same_units(&self, other: &Self) {
+        assert_eq!(
+            self.units, other.units,
+            "frequency series have different units"
+        );
+    }
+
+    fn zip_with<F: Fn(Complex<f64>, Complex<f64>) -> Complex<f64>>(
+        &self,
+        other: &Self,
+        function: F,
+    ) -> Self {
+        self.check_compatible(other);
+        Self {
+            data: self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .map(|(&a, &b)| function(a, b))
+                .collect(),
+            epoch: self.epoch,
+            units: self.units,
+            frequencies: self.frequencies.clone(),
+            delta_f: self.delta_f,
+        }
+    }
+}
+
+fn uniform_spacing(frequencies: &[f64]) -> Option<f64> {
+    if frequencies.len() < 2 {
+        return None;
+    }
+    let delta_f =
+        (frequencies[frequencies.len() - 1] - frequencies[0]) / (frequencies.len() - 1) as f64;
+    let tolerance = 1e-8 * delta_f.abs();
+    frequencies
+        .windows(2)
+        .all(|pair| (pair[1] - pair[0] - delta_f).abs() <= tolerance)
+        .then_some(delta_f)
+}
+
+impl Add for &FrequencySeries {
+    type Output = FrequencySeries;
+
+    fn add(self, other: Self) -> FrequencySeries {
+        self.check_same_units(other);
+        self.zip_with(other, |a, b| a + b)
+    }
+}
+
+impl Sub for &FrequencySeries {
+    type Output = FrequencySeries;
+
+    fn sub(self, other: Self) -> FrequencySeries {
+        self.check_same_units(other);
+        self.zip_with(other, |a, b| a - b)
+    }
+}
+
+impl Mul for &FrequencySeries {
+    type Output = FrequencySeries;
+
+    fn mul(self, other: Self) -> FrequencySeries {
+        let units = self.units.product(other.units).unwrap_or_else(|| {
+            panic!(
+                "cannot multiply frequency series in {:?} and {:?}",
+                self.units, other.units
+            )
+        });
+        let mut product = self.zip_with(other, |a, b| a * b);
+        product.units = units;
+        product
+    }
+}
+
+impl Mul<Complex<f64>> for &FrequencySeries {
+    type Output = FrequencySeries;
+
+    fn mul(self, other: Complex<f64>) -> FrequencySeries {
+        self.map(|value| value * other)
+    }
+}
+
+impl Mul<f64> for &FrequencySeries {
+    type Output = FrequencySeries;
+
+    fn mul(self, other: f64) -> FrequencySeries {
+        self.map(|value| value * other)
+    }
+}
+
+impl Neg for &FrequencySeries {
+    type Output = FrequencySeries;
+
+    fn neg(self) -> FrequencySeries {
+        self.map(|value| -value)
+    }
+}
+
+impl AddAssign<&FrequencySeries> for FrequencySeries {
+    fn add_assign(&mut self, other: &FrequencySeries) {
+        self.check_compatible(other);
+        self.check_same_units(other);
+        for (value, &increment) in self.data.iter_mut().zip(other.data.iter()) {
+            *value += increment;
+        }
+    }
+}
+
+impl SubAssign<&FrequencySeries> for FrequencySeries {
+    fn sub_assign(&mut self, other: &FrequencySeries) {
+        self.check_compatible(other);
+        self.check_same_units(other);
+        for (value, &decrement) in self.data.iter_mut().zip(other.data.iter()) {
+            *value -= decrement;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TimeSeries {
+    pub data: Vec<f64>,
+    pub delta_t: f64,
+    pub epoch: f64,
+    pub units: Unit,
+}
+
+impl TimeSeries {
+    pub fn new(data: Vec<f64>, delta_t: f64, epoch: f64, units: Unit) -> Self {
+        Self {
+            data,
+            delta_t,
+            epoch,
+            units,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.len() as f64 * self.delta_t
+    }
+
+    pub fn sampling_frequency(&self) -> f64 {
+        1.0 / self.delta_t
+    }
+
+    pub fn times(&self) -> Vec<f64> {
+        (0..self.len())
+            .map(|ii| self.epoch + ii as f64 * self.delta_t)
+            .collect()
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            data: self.data[range.clone()].to_vec(),
+            delta_t: self.delta_t,
+            epoch: self.epoch + range.start as f64 * self.delta_t,
+            units: self.units,
+        }
+    }
+
+    // The samples with t_start <= t < t_end.
+    pub fn time_slice(&self, t_start: f64, t_end: f64) -> Self {
+        let index = |time: f64| {
+            (((time - self.epoch) / self.delta_t).ceil().max(0.0) as usize).min(self.len())
+        };
+        let start = index(t_start);
+        self.slice(start..index(t_end).max(start))
+    }
+
+    pub fn map<F: Fn(f64) -> f64>(&self, function: F) -> Self {
+        Self {
+            data: self.data.iter().map(|&value| function(value)).collect(),
+            delta_t: self.delta_t,
+            epoch: self.epoch,
+            units: self.units,
+        }
+    }
+
+    fn check_same_units(&self, other: &Self) {
+        assert_eq!(self.units, other.units, "time series have different units");
+    }
+
+    fn zip_with<F: Fn(f64, f64) -> f64>(&self, other: &Self, function: F) -> Self {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "time series have different lengths"
+        );
+        assert!(
+            (self.delta_t - other.delta_t).abs() <= 1e-10 * self.delta_t
+                && (self.epoch - other.epoch).abs() <= 1e-3 * self.delta_t,
+            "time series are sampled at different times"
+        );
+        Self {
+            data: self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .map(|(&a, &b)| function(a, b))
+                .collect(),
+            delta_t: self.delta_t,
+            epoch: self.epoch,
+            units: self.units,
+        }
+    }
+}
+
+impl Add for &TimeSeries {
+    type Output = TimeSeries;
+
+    fn add(self, other: Self) -> TimeSeries {
+        self.check_same_units(other);
+        self.zip_with(other, |a, b| a + b)
+    }
+}
+
+impl Sub for &TimeSeries {
+    type Output = TimeSeries;
+
+    fn sub(self, other: Self) -> TimeSeries {
+        self.check_same_units(other);
+        self.zip_with(other, |a, b| a - b)
+    }
+}
+
+impl Mul for &TimeSeries {
+    type Output = TimeSeries;
+
+    fn mul(self, other: Self) -> TimeSeries {
+        let units = self.units.product(other.units).unwrap_or_else(|| {
+            panic!(
+                "cannot multiply time series in {:?} and {:?}",
+                self.units, other.units
+            )
+        });
+        let mut product = self.zip_with(other, |a, b| a * b);
+        product.units = units;
+        product
+    }
+}
+
+impl Mul<f64> for &TimeSeries {
+    type Output = TimeSeries;
+
+    fn mul(self, other: f64) -> TimeSeries {
+        self.map(|value| value * other)
+    }
+}
+
+impl Neg for &TimeSeries {
+    type Output = TimeSeries;
+
+    fn neg(self) -> TimeSeries {
+        self.map(|value| -value)
+    }
+}
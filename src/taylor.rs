@@ -1,64 +1,86 @@
-use std::f64::consts::PI;
-
 use crate::{
     constants::{MPC_SI, SOLAR_RADIUS_IN_M, SOLAR_RADIUS_IN_S},
+    cosmology::{detector_frame_mass, Cosmology},
+    numeric::{cast, Real},
+    parameters::BinaryParameters,
     pn::{PNPhasing, Params},
-    waveform::Waveform,
+    waveform::{Waveform, WaveformGenerator},
 };
 
+/// Post-Newtonian stationary-phase inspiral.
+///
+/// t = 0 is the formal coalescence time where the orbital speed diverges, the phase has no
+/// term linear in frequency.
 #[derive(Clone, Copy, Debug)]
-pub struct TaylorF2 {
-    params: Params,
-    pub phasing: PNPhasing,
-    pub total_mass: f64,
-    pub luminosity_distance: f64,
+pub struct TaylorF2<T = f64> {
+    params: Params<T>,
+    pub phasing: PNPhasing<T>,
+    pub total_mass: T,
+    pub luminosity_distance: T,
+    reference_frequency: f64,
+    reference_phase: T,
 }
 
-impl TaylorF2 {
+impl<T: Real> TaylorF2<T> {
+    /// The phase is referenced at `reference_frequency` in Hz, which must be positive and
+    /// finite.
     pub fn new(
-        total_mass: f64,
-        mass_ratio: f64,
-        chi_1: f64,
-        chi_2: f64,
-        luminosity_distance: f64,
+        total_mass: T,
+        mass_ratio: T,
+        chi_1: T,
+        chi_2: T,
+        luminosity_distance: T,
+        reference_frequency: f64,
     ) -> Self {
+        assert!(
+            reference_frequency.is_finite() && reference_frequency > 0.0,
+            "reference frequency {reference_frequency} Hz is not positive"
+        );
         let params = Params::new(mass_ratio, chi_1, chi_2);
         let mut new = Self {
             params,
             phasing: PNPhasing {
-                v: [0.0; 16],
-                vlogv: [0.0; 16],
-                vlogvlogv: [0.0; 16],
+                v: [T::zero(); 16],
+                vlogv: [T::zero(); 16],
+                vlogvlogv: [T::zero(); 16],
             },
             total_mass,
-            luminosity_distance: luminosity_distance * MPC_SI,
+            luminosity_distance: luminosity_distance * cast(MPC_SI),
+            reference_frequency,
+            reference_phase: T::zero(),
         };
         new.phasing = new.phasing_coefficients();
+        new.reference_phase = new.pn_phase(new.orbital_speed(cast(reference_frequency)));
         new
     }
 
-    fn phasing_coefficients(&self) -> PNPhasing {
-        PNPhasing::new(&self.params)
-    }
-}
-
-impl Waveform for TaylorF2 {
-    fn orbital_speed(&self, frequency: f64) -> f64 {
-        (PI * self.total_mass * SOLAR_RADIUS_IN_S * frequency).cbrt()
+    /// Build from the source-frame total mass at `redshift`, the luminosity distance
+    /// follows from `cosmology`.
+    pub fn from_source_frame(
+        source_frame_total_mass: T,
+        mass_ratio: T,
+        chi_1: T,
+        chi_2: T,
+        redshift: T,
+        cosmology: &Cosmology,
+        reference_frequency: f64,
+    ) -> Self {
+        Self::new(
+            detector_frame_mass(source_frame_total_mass, redshift),
+            mass_ratio,
+            chi_1,
+            chi_2,
+            cosmology.luminosity_distance(redshift),
+            reference_frequency,
+        )
     }
 
-    fn amplitude(&self, v: f64) -> f64 {
-        let mass_1 = self.total_mass / (1.0 + self.params.mass_ratio);
-        let mass_2 = self.total_mass - mass_1;
-        let amp_0: f64 =
-            -4.0 * mass_1 * mass_2 * SOLAR_RADIUS_IN_M * SOLAR_RADIUS_IN_S * (PI / 12.0).sqrt()
-                / self.luminosity_distance;
-        let d_energy_d_flux: f64 = 5.0 / 32.0 / self.params.eta / v.powi(9);
-        amp_0 * d_energy_d_flux.sqrt() * v
+    fn phasing_coefficients(&self) -> PNPhasing<T> {
+        PNPhasing::new(&self.params)
     }
 
-    fn phase(&self, v: f64, phi_c: f64) -> f64 {
-        let mut phasing = 0.0;
+    fn pn_phase(&self, v: T) -> T {
+        let mut phasing = T::zero();
         let mut cumulative_power_frequency = v.powi(-5);
         let log_orbital_speed = v.ln();
         for ii in 0..16 {
@@ -66,8 +88,103 @@ impl Waveform for TaylorF2 {
             phasing += self.phasing.vlogv[ii] * cumulative_power_frequency * log_orbital_speed;
             cumulative_power_frequency *= v;
         }
-        phasing -= 2.0 * phi_c + PI / 4.0;
+        phasing
+    }
+
+    // d pn_phase / dv
+    fn pn_phase_derivative(&self, v: T) -> T {
+        let mut derivative = T::zero();
+        let mut cumulative_power_frequency = v.powi(-6);
+        let log_orbital_speed = v.ln();
+        let mut power = cast::<T>(-5.0);
+        for ii in 0..16 {
+            derivative += power * self.phasing.v[ii] * cumulative_power_frequency;
+            derivative += self.phasing.vlogv[ii]
+                * cumulative_power_frequency
+                * (power * log_orbital_speed + T::one());
+            cumulative_power_frequency *= v;
+            power += T::one();
+        }
+        derivative
+    }
+}
+
+impl<T: Real> Waveform<T> for TaylorF2<T> {
+    fn total_mass(&self) -> T {
+        self.total_mass
+    }
+
+    fn params(&self) -> &Params<T> {
+        &self.params
+    }
+
+    fn luminosity_distance(&self) -> T {
+        self.luminosity_distance
+    }
+
+    fn reference_frequency(&self) -> f64 {
+        self.reference_frequency
+    }
+
+    fn orbital_speed(&self, frequency: T) -> T {
+        (T::PI() * self.total_mass * cast(SOLAR_RADIUS_IN_S) * frequency).cbrt()
+    }
+
+    fn amplitude(&self, v: T) -> T {
+        let c = cast::<T>;
+        let mass_1 = self.total_mass / (T::one() + self.params.mass_ratio);
+        let mass_2 = self.total_mass - mass_1;
+        let amp_0 = c(4.0)
+            * mass_1
+            * mass_2
+            * c(SOLAR_RADIUS_IN_M * SOLAR_RADIUS_IN_S)
+            * (T::PI() / c(12.0)).sqrt()
+            / self.luminosity_distance;
+        let d_energy_d_flux = (c(5.0) / c(32.0)) / self.params.eta / v.powi(9);
+        amp_0 * d_energy_d_flux.sqrt() * v
+    }
+
+    fn phase(&self, v: T, phi_c: T) -> T {
+        self.pn_phase(v) - self.reference_phase - cast::<T>(2.0) * phi_c
+    }
+
+    // the post-Newtonian expansion is not valid past the innermost stable circular orbit
+    fn end_frequency(&self) -> f64 {
+        self.f_isco()
+    }
+
+    fn phase_derivative(&self, frequency: T) -> T {
+        // v^3 is proportional to the frequency so dv / df = v / 3f
+        let v = self.orbital_speed(frequency);
+        self.pn_phase_derivative(v) * v / (cast::<T>(3.0) * frequency)
+    }
+}
+
+/// `TaylorF2` from `BinaryParameters` for any scalar type.
+#[derive(Clone, Copy, Debug)]
+pub struct TaylorF2Generator {
+    pub reference_frequency: f64,
+}
+
+impl TaylorF2Generator {
+    pub fn new(reference_frequency: f64) -> Self {
+        Self {
+            reference_frequency,
+        }
+    }
+}
+
+impl<T: Real> WaveformGenerator<T> for TaylorF2Generator {
+    type Output = TaylorF2<T>;
 
-        return phasing;
+    fn generate(&self, parameters: &BinaryParameters<T>) -> TaylorF2<T> {
+        TaylorF2::new(
+            parameters.total_mass,
+            parameters.mass_ratio,
+            parameters.chi_1,
+            parameters.chi_2,
+            parameters.luminosity_distance,
+            self.reference_frequency,
+        )
     }
 }
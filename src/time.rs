@@ -0,0 +1,84 @@
+use std::f64::consts::PI;
+
+use crate::{
+    constants::SPEED_OF_LIGHT_IN_VACUUM,
+    numeric::{cast, Real},
+};
+
+const GPS_EPOCH_JULIAN_DAY: f64 = 2444244.5;
+const GPS_EPOCH_UNIX_TIME: f64 = 315964800.0;
+const J2000_JULIAN_DAY: f64 = 2451545.0;
+const SECONDS_PER_DAY: f64 = 86400.0;
+// d GMST / dt in rad/s, the century terms change it by less than a part in 1e9
+pub(crate) const SIDEREAL_RATE: f64 = 360.98564736629 * PI / 180.0 / SECONDS_PER_DAY;
+
+// GPS times at which GPS - UTC increases by one second, update when IERS Bulletin C
+// announces a new leap second.
+const LEAP_SECOND_GPS_TIMES: [f64; 18] = [
+    46828800.0,
+    78364801.0,
+    109900802.0,
+    173059203.0,
+    252028804.0,
+    315187205.0,
+    346723206.0,
+    393984007.0,
+    425520008.0,
+    457056009.0,
+    504489610.0,
+    551750411.0,
+    599184012.0,
+    820108813.0,
+    914803214.0,
+    1025136015.0,
+    1119744016.0,
+    1167264017.0,
+];
+
+/// GPS - UTC in seconds.
+pub fn leap_seconds(gps_time: f64) -> f64 {
+    LEAP_SECOND_GPS_TIMES.partition_point(|&time| time <= gps_time) as f64
+}
+
+pub fn gps_to_unix(gps_time: f64) -> f64 {
+    gps_time + GPS_EPOCH_UNIX_TIME - leap_seconds(gps_time)
+}
+
+pub fn unix_to_gps(unix_time: f64) -> f64 {
+    let gps_time = unix_time - GPS_EPOCH_UNIX_TIME;
+    // the offset can change within the first estimate's leap seconds
+    let mut offset = leap_seconds(gps_time);
+    offset = leap_seconds(gps_time + offset);
+    gps_time + offset
+}
+
+/// Julian day in UTC, UT1 - UTC is below a second and ignored.
+pub fn gps_to_julian_day(gps_time: f64) -> f64 {
+    GPS_EPOCH_JULIAN_DAY + (gps_time - leap_seconds(gps_time)) / SECONDS_PER_DAY
+}
+
+/// Greenwich mean sidereal time in radians (Meeus, Astronomical Algorithms, 12.4).
+pub fn greenwich_mean_sidereal_time(gps_time: f64) -> f64 {
+    let days = gps_to_julian_day(gps_time) - J2000_JULIAN_DAY;
+    let centuries = days / 36525.0;
+    let degrees = 280.46061837 + 360.98564736629 * days + 0.000387933 * centuries.powi(2)
+        - centuries.powi(3) / 38710000.0;
+    degrees.to_radians().rem_euclid(2.0 * PI)
+}
+
+/// Arrival time at an Earth-fixed position minus the arrival time at the geocentre for a
+/// source at the given sky position.
+pub fn time_delay_from_geocenter(position: &[f64; 3], ra: f64, dec: f64, gps_time: f64) -> f64 {
+    time_delay_at_sidereal_time(position, ra, dec, greenwich_mean_sidereal_time(gps_time))
+}
+
+pub fn time_delay_at_sidereal_time<T: Real>(position: &[f64; 3], ra: T, dec: T, gmst: T) -> T {
+    let phi = ra - gmst;
+    let source = [dec.cos() * phi.cos(), dec.cos() * phi.sin(), dec.sin()];
+    -position
+        .iter()
+        .zip(source.iter())
+        .map(|(&x, &n)| cast::<T>(x) * n)
+        .sum::<T>()
+        / cast(SPEED_OF_LIGHT_IN_VACUUM)
+}
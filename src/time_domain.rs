@@ -0,0 +1,204 @@
+use std::f64::consts::PI;
+
+use num_complex::Complex;
+
+use crate::{
+    constants::SOLAR_RADIUS_IN_S,
+    fft::irfft,
+    pn::Params,
+    series::{TimeSeries, Unit},
+    waveform::Waveform,
+};
+
+// The conditioning follows XLALSimInspiralTDFromFD in LALSimulation.
+const EXTRA_TIME_FRACTION: f64 = 0.1;
+const EXTRA_CYCLES: f64 = 3.0;
+const HIGH_PASS_ATTENUATION: f64 = 0.99;
+const HIGH_PASS_ORDER: i32 = 8;
+
+/// Upper bound on the time from `frequency` to the end of the inspiral in seconds.
+pub fn chirp_time_bound(frequency: f64, total_mass: f64, params: &Params) -> f64 {
+    let mass_in_seconds = total_mass * SOLAR_RADIUS_IN_S;
+    let eta = params.eta;
+    let chi = params.chi_1.abs().max(params.chi_2.abs());
+
+    // TaylorT2 timing, the 1.5PN spin-orbit term is bounded using the largest spin and the
+    // (negative) 1.5PN tail term is dropped so this is an over-estimate.
+    let c0 = 5.0 * mass_in_seconds / (256.0 * eta);
+    let c2 = 743.0 / 252.0 + 11.0 / 3.0 * eta;
+    let c3 = 226.0 / 15.0 * chi;
+    let c4 = 3058673.0 / 508032.0 + 5429.0 / 504.0 * eta + 617.0 / 72.0 * eta.powi(2);
+    let v = (PI * mass_in_seconds * frequency).cbrt();
+    c0 * v.powi(-8) * (1.0 + (c2 + (c3 + c4 * v) * v) * v * v)
+}
+
+pub(crate) fn merge_time_bound(total_mass: f64) -> f64 {
+    // one orbit at r = 9M with v = c / 3
+    54.0 * PI * total_mass * SOLAR_RADIUS_IN_S
+}
+
+pub(crate) fn ringdown_time_bound(total_mass: f64, final_spin: f64) -> f64 {
+    // 11 e-folds of the l = m = 2, n = 0 mode using the fits in Table VIII of
+    // Berti, Cardoso and Will (2006)
+    let n_efolds = 11.0;
+    let omega = (1.5251 - 1.1568 * (1.0 - final_spin).powf(0.1292))
+        / (total_mass * SOLAR_RADIUS_IN_S);
+    let quality = 0.7 + 1.4187 * (1.0 - final_spin).powf(-0.499);
+    n_efolds * 2.0 * quality / omega
+}
+
+pub(crate) fn final_spin_bound(chi_1: f64, chi_2: f64) -> f64 {
+    // equal-mass fit of Tichy and Marronetti (2008) or the larger spin, capped at 0.998
+    (0.686 + 0.15 * (chi_1 + chi_2))
+        .max(chi_1.abs())
+        .max(chi_2.abs())
+        .min(0.998)
+}
+
+/// Conservative duration of the signal from `f_lower` through the ringdown in seconds,
+/// for choosing segment lengths.
+pub fn signal_duration_bound<W: Waveform>(model: &W, f_lower: f64) -> f64 {
+    let total_mass = model.total_mass();
+    let params = model.params();
+    let final_spin = final_spin_bound(params.chi_1, params.chi_2);
+    // near merger the PN bound can fall below the model's own time to merger
+    chirp_time_bound(f_lower, total_mass, params).max(-model.time_of_frequency(f_lower))
+        + merge_time_bound(total_mass)
+        + ringdown_time_bound(total_mass, final_spin)
+}
+
+pub(crate) fn chirp_start_frequency_bound(duration: f64, total_mass: f64, eta: f64) -> f64 {
+    let mass_in_seconds = total_mass * SOLAR_RADIUS_IN_S;
+    (5.0 * mass_in_seconds / (eta * duration)).powf(3.0 / 8.0) / (8.0 * PI * mass_in_seconds)
+}
+
+pub(crate) fn high_pass_response(frequency: f64, f_lower: f64) -> f64 {
+    // Magnitude response of a Butterworth filter applied forwards and backwards with
+    // the combined response equal to HIGH_PASS_ATTENUATION at f_lower.
+    let order = 2 * HIGH_PASS_ORDER;
+    let f_corner = f_lower * (1.0 / HIGH_PASS_ATTENUATION - 1.0).powf(1.0 / order as f64);
+    1.0 / (1.0 + (f_corner / frequency).powi(order))
+}
+
+pub trait TimeDomainWaveform {
+    fn total_mass(&self) -> f64;
+    fn params(&self) -> &Params;
+    fn luminosity_distance(&self) -> f64;
+    fn waveform_modes(
+        &self,
+        delta_t: f64,
+        f_lower: f64,
+        phi_c: f64,
+        t_c: f64,
+        theta_jn: f64,
+    ) -> (TimeSeries, TimeSeries);
+}
+
+/// Time-domain view of a frequency-domain model, t = 0 is the model's own t = 0.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeDomain<W> {
+    pub model: W,
+}
+
+impl<W: Waveform> TimeDomain<W> {
+    pub fn new(model: W) -> Self {
+        Self { model }
+    }
+}
+
+impl<W: Waveform> TimeDomainWaveform for TimeDomain<W> {
+    fn total_mass(&self) -> f64 {
+        self.model.total_mass()
+    }
+
+    fn params(&self) -> &Params {
+        self.model.params()
+    }
+
+    fn luminosity_distance(&self) -> f64 {
+        self.model.luminosity_distance()
+    }
+
+    fn waveform_modes(
+        &self,
+        delta_t: f64,
+        f_lower: f64,
+        phi_c: f64,
+        t_c: f64,
+        theta_jn: f64,
+    ) -> (TimeSeries, TimeSeries) {
+        time_domain_modes(&self.model, delta_t, f_lower, phi_c, t_c, theta_jn)
+    }
+}
+
+pub fn time_domain_modes<W: Waveform>(
+    model: &W,
+    delta_t: f64,
+    f_lower: f64,
+    phi_c: f64,
+    t_c: f64,
+    theta_jn: f64,
+) -> (TimeSeries, TimeSeries) {
+    let total_mass = model.total_mass();
+    let params = model.params();
+
+    // start no later than the lowest Kerr ISCO frequency
+    let f_isco = 1.0 / (9.0f64.powf(1.5) * PI * total_mass * SOLAR_RADIUS_IN_S);
+    let f_min = f_lower.min(f_isco);
+    let t_chirp = chirp_time_bound(f_min, total_mass, params);
+    let final_spin = final_spin_bound(params.chi_1, params.chi_2);
+    let t_merge = merge_time_bound(total_mass) + ringdown_time_bound(total_mass, final_spin);
+    let t_extra = EXTRA_CYCLES / f_min;
+
+    // generate from a lower frequency so the taper does not reach the requested band
+    let f_start =
+        chirp_start_frequency_bound((1.0 + EXTRA_TIME_FRACTION) * t_chirp, total_mass, params.eta);
+    let t_chirp = chirp_time_bound(f_start, total_mass, params);
+
+    let length = (((t_chirp + t_merge + 2.0 * t_extra) / delta_t).round() as usize)
+        .next_power_of_two();
+    let delta_f = 1.0 / (length as f64 * delta_t);
+    let k_start = ((f_start / delta_f).round() as usize).max(1);
+    let k_lower = ((f_min / delta_f).round() as usize).max(k_start);
+    let k_nyquist = length / 2;
+
+    let frequencies: Vec<f64> = (k_start..k_nyquist).map(|k| k as f64 * delta_f).collect();
+    let (plus, cross) = model.waveform_modes(&frequencies, phi_c, 0.0, theta_jn);
+
+    // shift the merger away from the end of the segment so the ringdown does not wrap
+    let shift_samples = (t_extra / delta_t).round() as usize;
+    let t_shift = shift_samples as f64 * delta_t;
+
+    let condition = |modes: &[Complex<f64>]| -> Vec<Complex<f64>> {
+        let mut conditioned = vec![Complex::new(0.0, 0.0); k_nyquist + 1];
+        for (k, &value) in (k_start..k_nyquist).zip(modes.iter()) {
+            let frequency = k as f64 * delta_f;
+            let taper = if k < k_lower {
+                0.5 - 0.5 * (PI * (k - k_start) as f64 / (k_lower - k_start) as f64).cos()
+            } else {
+                1.0
+            };
+            let shift = Complex::new(0.0, 2.0 * PI * frequency * t_shift).exp();
+            conditioned[k] = value * taper * high_pass_response(frequency, f_lower) * shift;
+        }
+        conditioned
+    };
+
+    // keep the estimated chirp, merger and the shifted ringdown
+    let keep = (((t_chirp + t_merge) / delta_t).round() as usize + shift_samples).min(length);
+    let start = length - keep;
+    // the peak of the model sits at t = 0 before moving it to t_c by shifting the epoch
+    let epoch = -1.0 / delta_f + t_shift + start as f64 * delta_t + t_c;
+
+    let to_time_domain = |modes: &[Complex<f64>]| -> TimeSeries {
+        let data = irfft(&condition(modes), length);
+        TimeSeries::new(
+            data[start..].iter().map(|&h| h * delta_f).collect(),
+            delta_t,
+            epoch,
+            Unit::Strain,
+        )
+    };
+
+    (to_time_domain(&plus.data), to_time_domain(&cross.data))
+}
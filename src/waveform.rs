@@ -1,37 +1,193 @@
 use num_complex::Complex;
 
-pub trait Waveform {
-    fn orbital_speed(&self, frequency: f64) -> f64;
-    fn phase(&self, v: f64, phi_c: f64) -> f64;
-    fn amplitude(&self, v: f64) -> f64;
+use std::f64::consts::PI;
 
-    fn waveform_single_frequency(&self, frequency: f64, phi_c: f64) -> Complex<f64> {
-        let orbital_speed = self.orbital_speed(frequency);
-        self.amplitude(orbital_speed)
-            * Complex::<f64> {
-                re: 0.0,
-                im: -self.phase(orbital_speed, phi_c),
+use crate::{
+    constants::SOLAR_RADIUS_IN_S,
+    numeric::{cast, Real},
+    parameters::BinaryParameters,
+    pn::{meco_orbital_speed, Params},
+    series::{FrequencySeries, Unit},
+};
+
+/// Frequency-domain waveform models.
+///
+/// The time of coalescence `t_c` applies exp(-2 pi i f t_c), moving the model's t = 0
+/// to t = t_c. Each model documents where its t = 0 sits.
+///
+/// `phi_c` is the orbital phase at the reference frequency, `phase` must return
+/// -2 phi_c there and `amplitude` must be positive so the strain at the reference
+/// frequency is |h| exp(2 i phi_c).
+///
+/// Models are generic over the scalar type `T`, with dual numbers the single frequency
+/// strain carries derivatives with respect to the parameters. Frequency series are
+/// always f64 and only keep the value.
+pub trait Waveform<T: Real = f64> {
+    fn total_mass(&self) -> T;
+    fn params(&self) -> &Params<T>;
+    // metres
+    fn luminosity_distance(&self) -> T;
+    fn reference_frequency(&self) -> f64;
+    fn orbital_speed(&self, frequency: T) -> T;
+    fn phase(&self, v: T, phi_c: T) -> T;
+    fn amplitude(&self, v: T) -> T;
+    // d phase / d frequency in seconds, phi_c and the reference phase do not contribute
+    fn phase_derivative(&self, frequency: T) -> T;
+    /// Highest frequency in Hz where the model is valid, the strain is zero above it.
+    fn end_frequency(&self) -> f64;
+
+    /// Innermost stable circular orbit of a Schwarzschild black hole with the total mass, Hz.
+    fn f_isco(&self) -> f64 {
+        let mass_in_seconds = self.total_mass().to_f64().unwrap() * SOLAR_RADIUS_IN_S;
+        1.0 / (6.0f64.powf(1.5) * PI * mass_in_seconds)
+    }
+
+    /// Minimum energy circular orbit of the post-Newtonian binding energy, Hz.
+    fn f_meco(&self) -> f64 {
+        let mass_in_seconds = self.total_mass().to_f64().unwrap() * SOLAR_RADIUS_IN_S;
+        let orbital_speed = meco_orbital_speed(self.params()).to_f64().unwrap();
+        orbital_speed.powi(3) / (PI * mass_in_seconds)
+    }
+
+    /// Stationary-phase time at which the signal passes through `frequency`, relative to
+    /// the model's t = 0.
+    ///
+    /// This is t(f) = -(1 / 2 pi) d arg(h) / df, so (1 / 2 pi) d phase / df with the sign
+    /// of `phase`, and is negative before the merger.
+    fn time_of_frequency(&self, frequency: T) -> T {
+        self.phase_derivative(frequency) / (cast::<T>(2.0) * T::PI())
+    }
+
+    /// Inverse of `time_of_frequency` by bisection in log frequency.
+    ///
+    /// t(f) is only invertible where it increases monotonically, so the search is
+    /// restricted to a band `f_lower` to `f_upper`. None if `time` is not reached in the band.
+    fn frequency_of_time(&self, time: f64, f_lower: f64, f_upper: f64) -> Option<f64> {
+        let time_of = |frequency: f64| self.time_of_frequency(cast(frequency)).to_f64().unwrap();
+        if !(time_of(f_lower) <= time && time <= time_of(f_upper)) {
+            return None;
+        }
+        let (mut lower, mut upper) = (f_lower.ln(), f_upper.ln());
+        while upper - lower > 4.0 * f64::EPSILON * upper.abs().max(1.0) {
+            let middle = (lower + upper) / 2.0;
+            if time_of(middle.exp()) < time {
+                lower = middle;
+            } else {
+                upper = middle;
             }
-            .exp()
+        }
+        Some(((lower + upper) / 2.0).exp())
+    }
+
+    fn waveform_single_frequency(&self, frequency: T, phi_c: T, t_c: T) -> Complex<T> {
+        if frequency > cast(self.end_frequency()) {
+            return Complex::new(T::zero(), T::zero());
+        }
+        let orbital_speed = self.orbital_speed(frequency);
+        Complex::from_polar(
+            self.amplitude(orbital_speed),
+            -self.phase(orbital_speed, phi_c) - cast::<T>(2.0) * T::PI() * frequency * t_c,
+        )
     }
-    fn waveform(&self, frequencies: &[f64], phi_c: f64) -> Vec<Complex<f64>> {
-        frequencies
-            .iter()
-            .map(|&f| self.waveform_single_frequency(f, phi_c))
-            .collect()
+    fn waveform(&self, frequencies: &[f64], phi_c: f64, t_c: f64) -> FrequencySeries {
+        let mut output = FrequencySeries::zeros(frequencies.to_vec(), 0.0, Unit::StrainPerHertz);
+        self.fill_waveform(phi_c, t_c, &mut output);
+        output
+    }
+
+    /// `waveform` written in place at the frequencies of `output`.
+    fn fill_waveform(&self, phi_c: f64, t_c: f64, output: &mut FrequencySeries) {
+        for ii in 0..output.len() {
+            let frequency = output.frequencies()[ii];
+            let value = self.waveform_single_frequency(cast(frequency), cast(phi_c), cast(t_c));
+            output.data[ii] = Complex::new(value.re.to_f64().unwrap(), value.im.to_f64().unwrap());
+        }
+        output.epoch = 0.0;
+        output.units = Unit::StrainPerHertz;
     }
 
     fn waveform_modes(
         &self,
         frequencies: &[f64],
         phi_c: f64,
+        t_c: f64,
         theta_jn: f64,
-    ) -> (Vec<Complex<f64>>, Vec<Complex<f64>>) {
-        let waveform = self.waveform(frequencies, phi_c);
-        let plus_factor = (1.0 + theta_jn.cos().powi(2)) / 2.0;
-        let cross_factor = -Complex::I * theta_jn.cos();
-        let plus = waveform.iter().map(|&w| w * plus_factor).collect();
-        let cross = waveform.iter().map(|&w| w * cross_factor).collect();
+    ) -> (FrequencySeries, FrequencySeries) {
+        let mut plus = FrequencySeries::zeros(frequencies.to_vec(), 0.0, Unit::StrainPerHertz);
+        let mut cross = plus.clone();
+        self.fill_waveform_modes(phi_c, t_c, theta_jn, &mut plus, &mut cross);
         (plus, cross)
     }
+
+    /// Plus and cross polarisations at a single frequency, as `waveform_single_frequency`
+    /// for the dominant mode seen at the inclination `theta_jn`.
+    fn modes_single_frequency(
+        &self,
+        frequency: T,
+        phi_c: T,
+        t_c: T,
+        theta_jn: T,
+    ) -> (Complex<T>, Complex<T>) {
+        let strain = self.waveform_single_frequency(frequency, phi_c, t_c);
+        let cos_iota = theta_jn.cos();
+        let plus_factor = (T::one() + cos_iota * cos_iota) / cast(2.0);
+        (
+            strain * plus_factor,
+            strain * Complex::new(T::zero(), -cos_iota),
+        )
+    }
+
+    /// `waveform_modes` written in place at the frequencies of `plus`, `cross` must have
+    /// the same length.
+    fn fill_waveform_modes(
+        &self,
+        phi_c: f64,
+        t_c: f64,
+        theta_jn: f64,
+        plus: &mut FrequencySeries,
+        cross: &mut FrequencySeries,
+    ) {
+        assert_eq!(
+            plus.len(),
+            cross.len(),
+            "polarisations have different lengths"
+        );
+        let to_f64 = |value: Complex<T>| {
+            Complex::new(value.re.to_f64().unwrap(), value.im.to_f64().unwrap())
+        };
+        for ii in 0..plus.len() {
+            let frequency = plus.frequencies()[ii];
+            let (plus_value, cross_value) = self.modes_single_frequency(
+                cast(frequency),
+                cast(phi_c),
+                cast(t_c),
+                cast(theta_jn),
+            );
+            plus.data[ii] = to_f64(plus_value);
+            cross.data[ii] = to_f64(cross_value);
+        }
+        for series in [plus, cross] {
+            series.epoch = 0.0;
+            series.units = Unit::StrainPerHertz;
+        }
+    }
+}
+
+/// Builds a model from the parameters of a binary.
+///
+/// Closures returning an f64 model implement this for f64. Generators implementing it for
+/// every `Real` type, such as `IMRPhenomDGenerator`, also give dual-number derivatives
+/// through the same constructor.
+pub trait WaveformGenerator<T: Real = f64> {
+    type Output: Waveform<T>;
+
+    fn generate(&self, parameters: &BinaryParameters<T>) -> Self::Output;
+}
+
+impl<W: Waveform, F: Fn(&BinaryParameters) -> W> WaveformGenerator for F {
+    type Output = W;
+
+    fn generate(&self, parameters: &BinaryParameters) -> W {
+        self(parameters)
+    }
 }